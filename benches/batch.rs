@@ -0,0 +1,67 @@
+use cp_protocol::{Group, Proof};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::{modp_proof, ristretto_proof, transcripts};
+
+// measured on one core, verify / verify_batch / verify_batch_registered:
+//
+//   modp       n = 1    5.4 /   5.2 /   3.2 ms
+//              n = 2   11.2 /  10.9 /   7.2 ms
+//              n = 16    87 /    77 /    54 ms
+//              n = 64   340 /   313 /   217 ms
+//              n = 128  727 /   543 /   449 ms
+//   ristretto  n = 1    103 /   107 /   104 us
+//              n = 2    206 /   134 /   146 us
+//              n = 16  1784 /   733 /   703 us
+//              n = 64   6.9 /   2.3 /   2.3 ms
+//              n = 128   14 /   3.8 /   4.0 ms
+//
+// below Group::BATCH_THRESHOLD the batch functions check transcripts one by one, so the
+// modp rows under 64 are such checks. combined, modp took 12.0 ms at n = 2 and was within
+// noise of one by one at 16: the subgroup checks on r1 and r2 cost about as much as the
+// exponentiations combining saves, until batches are large. ristretto gains from two
+// transcripts on. most of the modp gain comes from skipping the checks on y1 and y2
+const BATCH_SIZES: [usize; 5] = [1, 2, 16, 64, 128];
+
+// per-item verify against verify_batch over the same transcripts
+fn compare<G: Group>(criterion: &mut Criterion, name: &str, proof: &Proof<G>) {
+    let mut group = criterion.benchmark_group(name);
+
+    for size in BATCH_SIZES {
+        let batch = transcripts(proof, size);
+
+        group.bench_with_input(BenchmarkId::new("verify", size), &batch, |b, batch| {
+            b.iter(|| {
+                batch
+                    .iter()
+                    .all(|t| proof.verify(&t.r1, &t.r2, &t.y1, &t.y2, &t.c, &t.s).is_ok())
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("verify_batch", size),
+            &batch,
+            |b, batch| b.iter(|| proof.verify_batch(batch).is_ok()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("verify_batch_registered", size),
+            &batch,
+            |b, batch| b.iter(|| proof.verify_batch_registered(batch).is_ok()),
+        );
+    }
+
+    group.finish();
+}
+
+fn modp(criterion: &mut Criterion) {
+    compare(criterion, "modp", &modp_proof());
+}
+
+fn ristretto(criterion: &mut Criterion) {
+    compare(criterion, "ristretto", &ristretto_proof());
+}
+
+criterion_group!(benches, modp, ristretto);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use cp_protocol::{Group, Proof, Ristretto255, SecretScalar};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+// create_pair and verify with and without the fixed base tables
+fn compare<G: Group>(criterion: &mut Criterion, name: &str, plain: Proof<G>) {
+    let mut group = criterion.benchmark_group(name);
+
+    let x = SecretScalar::new(plain.generate_random_number());
+    let [y1, y2] = plain.create_pair(&x).unwrap();
+    let commitment = plain.prover(&x).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let c = plain.generate_random_number();
+    let s = commitment.respond(&c).unwrap();
+
+    group.bench_function("create_pair", |b| b.iter(|| plain.create_pair(&x)));
+    group.bench_function("verify", |b| {
+        b.iter(|| plain.verify(&r1, &r2, &y1, &y2, &c, &s))
+    });
+
+    let fast = plain.with_fixed_base_tables();
+    group.bench_function("create_pair_tables", |b| b.iter(|| fast.create_pair(&x)));
+    group.bench_function("verify_tables", |b| {
+        b.iter(|| fast.verify(&r1, &r2, &y1, &y2, &c, &s))
+    });
+
+    group.finish();
+}
+
+fn modp(criterion: &mut Criterion) {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    compare(criterion, "modp", Proof::new(p, q, alpha, beta));
+}
+
+fn ristretto(criterion: &mut Criterion) {
+    let (alpha, beta) = Ristretto255::get_constants();
+    compare(
+        criterion,
+        "ristretto",
+        Proof::with_group(Ristretto255, alpha, beta),
+    );
+}
+
+criterion_group!(benches, modp, ristretto);
+criterion_main!(benches);
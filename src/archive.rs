@@ -0,0 +1,445 @@
+use num_bigint::BigUint;
+use serde::de::{self, DeserializeOwned, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::Proof;
+use crate::batch;
+use crate::error::Error;
+use crate::group::{Group, ModpGroup};
+use crate::params::MinimumStrength;
+use crate::ristretto::Ristretto255;
+
+// portable file format for parameters, public keys and transcripts, so runs of the protocol
+// can be archived and re-verified later. every value is stored in the canonical encoding of
+// its group, as hex in JSON and as a byte string in CBOR, and every file is an envelope
+//
+//   { "version": 1, "kind": "archive", "body": { ... } }
+//
+// whose version and kind are checked before the body is read
+
+// bumped on any incompatible change to the body of any kind
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Json(serde_json::Error),
+    Cbor(String),
+    UnsupportedVersion(u32),
+    // the envelope holds a different kind of payload than the one asked for
+    UnexpectedKind {
+        expected: &'static str,
+        found: String,
+    },
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Json(error) => write!(f, "invalid json: {}", error),
+            ArchiveError::Cbor(reason) => write!(f, "invalid cbor: {}", reason),
+            ArchiveError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported format version {} (expected {})",
+                version, FORMAT_VERSION
+            ),
+            ArchiveError::UnexpectedKind { expected, found } => {
+                write!(f, "expected a {} but found a {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArchiveError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// hex string in human readable formats, byte string otherwise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(&self.0))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+}
+
+// takes either form whatever the format claims to be, serde buffers the fields of the
+// internally tagged Parameters and reports the buffer as human readable even for CBOR
+struct BytesVisitor;
+
+impl Visitor<'_> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a hex or byte string")
+    }
+
+    fn visit_str<E: de::Error>(self, hex_string: &str) -> Result<Bytes, E> {
+        hex::decode(hex_string).map(Bytes).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes(bytes.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Bytes, E> {
+        Ok(Bytes(bytes))
+    }
+}
+
+// group and generators of a Proof. p and q are minimal big endian, alpha and beta use the
+// element encoding of their group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "group", rename_all = "lowercase")]
+pub enum Parameters {
+    Modp {
+        p: Bytes,
+        q: Bytes,
+        alpha: Bytes,
+        beta: Bytes,
+    },
+    Ristretto255 {
+        alpha: Bytes,
+        beta: Bytes,
+    },
+}
+
+impl Parameters {
+    pub fn modp(proof: &Proof) -> Self {
+        let group = proof.group();
+        Parameters::Modp {
+            p: Bytes(group.p().to_bytes_be()),
+            q: Bytes(group.q().to_bytes_be()),
+            alpha: Bytes(group.encode_element(&proof.alpha)),
+            beta: Bytes(group.encode_element(&proof.beta)),
+        }
+    }
+
+    pub fn ristretto255(proof: &Proof<Ristretto255>) -> Self {
+        Parameters::Ristretto255 {
+            alpha: Bytes(Ristretto255.encode_element(&proof.alpha)),
+            beta: Bytes(Ristretto255.encode_element(&proof.beta)),
+        }
+    }
+
+    // parameters read from a file are untrusted, they go through the same validation as
+    // Proof::try_new
+    pub fn modp_proof(&self, minimum: &MinimumStrength) -> Result<Proof, Error> {
+        let Parameters::Modp { p, q, alpha, beta } = self else {
+            return Err(Error::Encoding("parameters"));
+        };
+
+        let (p, q) = (decode_modulus("p", p)?, decode_modulus("q", q)?);
+        let group = ModpGroup::new(p.clone(), q.clone());
+        let alpha = group
+            .decode_element(&alpha.0)
+            .ok_or(Error::Encoding("alpha"))?;
+        let beta = group
+            .decode_element(&beta.0)
+            .ok_or(Error::Encoding("beta"))?;
+
+        Proof::try_new(p, q, alpha, beta, minimum)
+    }
+
+    pub fn ristretto255_proof(&self) -> Result<Proof<Ristretto255>, Error> {
+        let Parameters::Ristretto255 { alpha, beta } = self else {
+            return Err(Error::Encoding("parameters"));
+        };
+
+        let decode = |name, bytes: &Bytes| {
+            let element = Ristretto255
+                .decode_element(&bytes.0)
+                .ok_or(Error::Encoding(name))?;
+            if element == Ristretto255.identity() {
+                return Err(Error::InvalidElement(name));
+            }
+            Ok(element)
+        };
+
+        Ok(Proof::with_group(
+            Ristretto255,
+            decode("alpha", alpha)?,
+            decode("beta", beta)?,
+        ))
+    }
+}
+
+// minimal big endian, so a leading zero byte is as invalid as an empty string
+fn decode_modulus(name: &'static str, bytes: &Bytes) -> Result<BigUint, Error> {
+    match bytes.0.first() {
+        Some(&first) if first != 0 => Ok(BigUint::from_bytes_be(&bytes.0)),
+        _ => Err(Error::Encoding(name)),
+    }
+}
+
+// y1 = alpha^x, y2 = beta^x
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKey {
+    pub y1: Bytes,
+    pub y2: Bytes,
+}
+
+// r1 = alpha^k, r2 = beta^k
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Commitment {
+    pub r1: Bytes,
+    pub r2: Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Challenge {
+    pub c: Bytes,
+}
+
+// s = k - c * x mod q
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    pub s: Bytes,
+}
+
+// one complete run, see batch::Transcript
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub public_key: PublicKey,
+    pub commitment: Commitment,
+    pub challenge: Challenge,
+    pub response: Response,
+}
+
+// parameters together with the transcripts that ran under them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Archive {
+    pub parameters: Parameters,
+    pub transcripts: Vec<Transcript>,
+}
+
+// the encode functions take values the caller already holds as valid, the decode functions
+// take file contents and check them like any value received over the wire
+impl PublicKey {
+    pub fn encode<G: Group>(proof: &Proof<G>, y1: &G::Element, y2: &G::Element) -> Self {
+        PublicKey {
+            y1: Bytes(proof.group().encode_element(y1)),
+            y2: Bytes(proof.group().encode_element(y2)),
+        }
+    }
+
+    pub fn decode<G: Group>(&self, proof: &Proof<G>) -> Result<[G::Element; 2], Error> {
+        Ok([
+            proof.decode_element("y1", &self.y1.0)?,
+            proof.decode_element("y2", &self.y2.0)?,
+        ])
+    }
+}
+
+impl Commitment {
+    pub fn encode<G: Group>(proof: &Proof<G>, r1: &G::Element, r2: &G::Element) -> Self {
+        Commitment {
+            r1: Bytes(proof.group().encode_element(r1)),
+            r2: Bytes(proof.group().encode_element(r2)),
+        }
+    }
+
+    pub fn decode<G: Group>(&self, proof: &Proof<G>) -> Result<[G::Element; 2], Error> {
+        Ok([
+            proof.decode_element("r1", &self.r1.0)?,
+            proof.decode_element("r2", &self.r2.0)?,
+        ])
+    }
+}
+
+impl Challenge {
+    pub fn encode<G: Group>(proof: &Proof<G>, c: &G::Scalar) -> Self {
+        Challenge {
+            c: Bytes(proof.group().encode_scalar(c)),
+        }
+    }
+
+    pub fn decode<G: Group>(&self, proof: &Proof<G>) -> Result<G::Scalar, Error> {
+        proof.decode_scalar("c", &self.c.0)
+    }
+}
+
+impl Response {
+    pub fn encode<G: Group>(proof: &Proof<G>, s: &G::Scalar) -> Self {
+        Response {
+            s: Bytes(proof.group().encode_scalar(s)),
+        }
+    }
+
+    pub fn decode<G: Group>(&self, proof: &Proof<G>) -> Result<G::Scalar, Error> {
+        proof.decode_scalar("s", &self.s.0)
+    }
+}
+
+impl Transcript {
+    pub fn encode<G: Group>(proof: &Proof<G>, transcript: &batch::Transcript<G>) -> Self {
+        Transcript {
+            public_key: PublicKey::encode(proof, &transcript.y1, &transcript.y2),
+            commitment: Commitment::encode(proof, &transcript.r1, &transcript.r2),
+            challenge: Challenge::encode(proof, &transcript.c),
+            response: Response::encode(proof, &transcript.s),
+        }
+    }
+
+    pub fn decode<G: Group>(&self, proof: &Proof<G>) -> Result<batch::Transcript<G>, Error> {
+        let [y1, y2] = self.public_key.decode(proof)?;
+        let [r1, r2] = self.commitment.decode(proof)?;
+        Ok(batch::Transcript {
+            r1,
+            r2,
+            y1,
+            y2,
+            c: self.challenge.decode(proof)?,
+            s: self.response.decode(proof)?,
+        })
+    }
+}
+
+impl<G: Group> Proof<G> {
+    // decodes and batch verifies archived transcripts, failing like verify_batch with the
+    // index and error of every transcript that does not decode or does not verify
+    pub fn verify_archived(&self, transcripts: &[Transcript]) -> Result<(), Vec<(usize, Error)>> {
+        let mut failures = Vec::new();
+        let mut decoded = Vec::with_capacity(transcripts.len());
+        let mut indices = Vec::with_capacity(transcripts.len());
+        for (index, transcript) in transcripts.iter().enumerate() {
+            match transcript.decode(self) {
+                Ok(transcript) => {
+                    decoded.push(transcript);
+                    indices.push(index);
+                }
+                Err(error) => failures.push((index, error)),
+            }
+        }
+
+        if let Err(errors) = self.verify_batch(&decoded) {
+            failures.extend(errors.into_iter().map(|(i, error)| (indices[i], error)));
+            failures.sort_by_key(|(index, _)| *index);
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+// a type that can be the body of an envelope, KIND names it inside the file
+pub trait Payload: Serialize + DeserializeOwned {
+    const KIND: &'static str;
+}
+
+impl Payload for Parameters {
+    const KIND: &'static str = "parameters";
+}
+
+impl Payload for PublicKey {
+    const KIND: &'static str = "public_key";
+}
+
+impl Payload for Commitment {
+    const KIND: &'static str = "commitment";
+}
+
+impl Payload for Challenge {
+    const KIND: &'static str = "challenge";
+}
+
+impl Payload for Response {
+    const KIND: &'static str = "response";
+}
+
+impl Payload for Transcript {
+    const KIND: &'static str = "transcript";
+}
+
+impl Payload for Archive {
+    const KIND: &'static str = "archive";
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    kind: &'a str,
+    body: &'a T,
+}
+
+// read on its own first, so an unknown version is reported as such rather than as
+// whatever its body fails to parse with
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct Body<T> {
+    body: T,
+}
+
+fn envelope<T: Payload>(body: &T) -> Envelope<'_, T> {
+    Envelope {
+        version: FORMAT_VERSION,
+        kind: T::KIND,
+        body,
+    }
+}
+
+fn check_header<T: Payload>(header: Header) -> Result<(), ArchiveError> {
+    if header.version != FORMAT_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(header.version));
+    }
+    if header.kind != T::KIND {
+        return Err(ArchiveError::UnexpectedKind {
+            expected: T::KIND,
+            found: header.kind,
+        });
+    }
+    Ok(())
+}
+
+pub fn to_json<T: Payload>(body: &T) -> Result<String, ArchiveError> {
+    serde_json::to_string_pretty(&envelope(body)).map_err(ArchiveError::Json)
+}
+
+pub fn from_json<T: Payload>(json: &str) -> Result<T, ArchiveError> {
+    check_header::<T>(serde_json::from_str(json).map_err(ArchiveError::Json)?)?;
+    let envelope: Body<T> = serde_json::from_str(json).map_err(ArchiveError::Json)?;
+    Ok(envelope.body)
+}
+
+pub fn to_cbor<T: Payload>(body: &T) -> Result<Vec<u8>, ArchiveError> {
+    let mut out = Vec::new();
+    ciborium::into_writer(&envelope(body), &mut out)
+        .map_err(|error| ArchiveError::Cbor(error.to_string()))?;
+    Ok(out)
+}
+
+pub fn from_cbor<T: Payload>(cbor: &[u8]) -> Result<T, ArchiveError> {
+    let invalid = |error: &dyn fmt::Display| ArchiveError::Cbor(error.to_string());
+
+    check_header::<T>(ciborium::from_reader(cbor).map_err(|e| invalid(&e))?)?;
+    let envelope: Body<T> = ciborium::from_reader(cbor).map_err(|e| invalid(&e))?;
+    Ok(envelope.body)
+}
@@ -0,0 +1,221 @@
+use rand_core::{CryptoRngCore, OsRng};
+
+use crate::error::{Equation, Error};
+use crate::group::Group;
+use crate::{Generator, NonInteractiveProof, Proof};
+
+// everything the verifier needs to check one Chaum-Pedersen run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript<G: Group> {
+    pub r1: G::Element,
+    pub r2: G::Element,
+    pub y1: G::Element,
+    pub y2: G::Element,
+    pub c: G::Scalar,
+    pub s: G::Scalar,
+}
+
+impl<G: Group> Proof<G> {
+    // recomputes the Fiat-Shamir challenge so a login proof can join a batch
+    pub fn login_transcript(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        identifier: &str,
+        nonce: &[u8],
+        proof: &NonInteractiveProof<G>,
+    ) -> Transcript<G> {
+        Transcript {
+            c: self.challenge(y1, y2, &proof.r1, &proof.r2, identifier, nonce),
+            r1: proof.r1.clone(),
+            r2: proof.r2.clone(),
+            y1: y1.clone(),
+            y2: y2.clone(),
+            s: proof.s.clone(),
+        }
+    }
+
+    // accepts exactly the transcripts verify would accept, except with probability 2^-128.
+    // on failure returns the index and error of every bad transcript, in order
+    pub fn verify_batch(&self, transcripts: &[Transcript<G>]) -> Result<(), Vec<(usize, Error)>> {
+        self.verify_batch_with_rng(transcripts, &mut OsRng)
+    }
+
+    pub fn verify_batch_with_rng(
+        &self,
+        transcripts: &[Transcript<G>],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<(), Vec<(usize, Error)>> {
+        self.verify_transcripts(transcripts, true, rng)
+    }
+
+    // for y1 and y2 that were registered keys, already checked by decode_element at
+    // registration. skips their subgroup checks, which on modp cost as much as the rest
+    // of the batch. only pass keys that really went through that check
+    pub fn verify_batch_registered(
+        &self,
+        transcripts: &[Transcript<G>],
+    ) -> Result<(), Vec<(usize, Error)>> {
+        self.verify_batch_registered_with_rng(transcripts, &mut OsRng)
+    }
+
+    pub fn verify_batch_registered_with_rng(
+        &self,
+        transcripts: &[Transcript<G>],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<(), Vec<(usize, Error)>> {
+        self.verify_transcripts(transcripts, false, rng)
+    }
+
+    fn verify_transcripts(
+        &self,
+        transcripts: &[Transcript<G>],
+        check_keys: bool,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<(), Vec<(usize, Error)>> {
+        let mut failures = Vec::new();
+
+        if transcripts.len() < G::BATCH_THRESHOLD {
+            for (index, t) in transcripts.iter().enumerate() {
+                let result = if check_keys {
+                    self.verify(&t.r1, &t.r2, &t.y1, &t.y2, &t.c, &t.s)
+                } else {
+                    self.verify_registered(t)
+                };
+                if let Err(error) = result {
+                    failures.push((index, error));
+                }
+            }
+        } else {
+            let mut indices = Vec::with_capacity(transcripts.len());
+
+            // the combined equation is only sound for subgroup elements and reduced scalars
+            for (index, transcript) in transcripts.iter().enumerate() {
+                match self.check_transcript(transcript, check_keys) {
+                    Ok(()) => indices.push(index),
+                    Err(error) => failures.push((index, error)),
+                }
+            }
+
+            self.bisect(transcripts, &indices, rng, &mut failures);
+            failures.sort_by_key(|(index, _)| *index);
+        }
+
+        if failures.is_empty() {
+            return Ok(());
+        }
+        Err(failures)
+    }
+
+    // Proof::verify without the checks on y1 and y2
+    fn verify_registered(&self, t: &Transcript<G>) -> Result<(), Error> {
+        self.check_scalar("c", &t.c)?;
+        self.check_scalar("s", &t.s)?;
+
+        if let Err(equation) = self.check_equations(&t.r1, &t.r2, &t.y1, &t.y2, &t.c, &t.s) {
+            match equation {
+                Equation::R1 => self.check_element("r1", &t.r1)?,
+                Equation::R2 => self.check_element("r2", &t.r2)?,
+            }
+            return Err(Error::VerificationFailed(equation));
+        }
+        Ok(())
+    }
+
+    fn check_transcript(&self, transcript: &Transcript<G>, check_keys: bool) -> Result<(), Error> {
+        self.check_element("r1", &transcript.r1)?;
+        self.check_element("r2", &transcript.r2)?;
+        if check_keys {
+            self.check_element("y1", &transcript.y1)?;
+            self.check_element("y2", &transcript.y2)?;
+        }
+        self.check_scalar("c", &transcript.c)?;
+        self.check_scalar("s", &transcript.s)
+    }
+
+    // checks the whole range at once and only splits it when the combination fails,
+    // so a batch with b bad transcripts costs about 2b log n combined checks
+    fn bisect(
+        &self,
+        transcripts: &[Transcript<G>],
+        indices: &[usize],
+        rng: &mut dyn CryptoRngCore,
+        failures: &mut Vec<(usize, Error)>,
+    ) {
+        match indices {
+            [] => {}
+            [index] => {
+                let t = &transcripts[*index];
+                // already checked by check_transcript
+                if let Err(equation) = self.check_equations(&t.r1, &t.r2, &t.y1, &t.y2, &t.c, &t.s)
+                {
+                    failures.push((*index, Error::VerificationFailed(equation)));
+                }
+            }
+            _ => {
+                if self.combination_holds(transcripts, indices, rng) {
+                    return;
+                }
+                let (left, right) = indices.split_at(indices.len() / 2);
+                self.bisect(transcripts, left, rng, failures);
+                self.bisect(transcripts, right, rng, failures);
+            }
+        }
+    }
+
+    // with fresh random 128 bit weights d_i and e_i, checks
+    // prod r1_i^d_i * r2_i^e_i * y1_i^-(d_i c_i) * y2_i^-(e_i c_i) * alpha^-(sum d_i s_i) * beta^-(sum e_i s_i) == 1
+    // in a single multi-exponentiation. a bad transcript survives only if the weights
+    // happen to cancel its error
+    fn combination_holds(
+        &self,
+        transcripts: &[Transcript<G>],
+        indices: &[usize],
+        rng: &mut dyn CryptoRngCore,
+    ) -> bool {
+        let group = &self.group;
+        let zero = group.scalar_from_u128(0);
+        let mut weight = || {
+            let mut bytes = [0u8; 16];
+            rng.fill_bytes(&mut bytes);
+            group.scalar_from_u128(u128::from_le_bytes(bytes))
+        };
+
+        let mut alpha_exponent = zero.clone();
+        let mut beta_exponent = zero.clone();
+        let mut exponents = Vec::with_capacity(4 * indices.len());
+
+        for index in indices {
+            let t = &transcripts[*index];
+            let (d, e) = (weight(), weight());
+
+            alpha_exponent = group.scalar_add(&alpha_exponent, &group.scalar_mul(&d, &t.s));
+            beta_exponent = group.scalar_add(&beta_exponent, &group.scalar_mul(&e, &t.s));
+
+            let y1_exponent = group.scalar_sub(&zero, &group.scalar_mul(&d, &t.c));
+            let y2_exponent = group.scalar_sub(&zero, &group.scalar_mul(&e, &t.c));
+            exponents.extend([d, e, y1_exponent, y2_exponent]);
+        }
+
+        let terms: Vec<_> = indices
+            .iter()
+            .flat_map(|index| {
+                let t = &transcripts[*index];
+                [&t.r1, &t.r2, &t.y1, &t.y2]
+            })
+            .zip(&exponents)
+            .collect();
+
+        let alpha_exponent = group.scalar_sub(&zero, &alpha_exponent);
+        let beta_exponent = group.scalar_sub(&zero, &beta_exponent);
+        let product = self.multi_exp_generators(
+            &[
+                (Generator::Alpha, &alpha_exponent),
+                (Generator::Beta, &beta_exponent),
+            ],
+            &terms,
+        );
+
+        product == group.identity()
+    }
+}
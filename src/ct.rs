@@ -0,0 +1,144 @@
+use crypto_bigint::modular::runtime_mod::{DynResidue, DynResidueParams};
+use crypto_bigint::{U1024, U2048, U3072, U4096, U6144, U8192, Uint};
+use num_bigint::BigUint;
+use zeroize::{Zeroize, Zeroizing};
+
+// constant time modp arithmetic for values derived from x or k, on fixed width
+// Montgomery residues. the running time depends only on the bit lengths of the
+// modulus and the exponent bound, never on the secret values. inputs and outputs are
+// BigUint, whose length still reveals leading zero bytes, everything in between
+// runs on limbs of the smallest supported width holding the modulus.
+// every function returns None when the modulus is even or wider than 8192 bits
+
+// calls $function::<LIMBS>(args) for the smallest width holding $bits
+macro_rules! with_width {
+    ($bits:expr, $function:ident($($arg:expr),*)) => {
+        match $bits {
+            0..=1024 => $function::<{ U1024::LIMBS }>($($arg),*),
+            1025..=2048 => $function::<{ U2048::LIMBS }>($($arg),*),
+            2049..=3072 => $function::<{ U3072::LIMBS }>($($arg),*),
+            3073..=4096 => $function::<{ U4096::LIMBS }>($($arg),*),
+            4097..=6144 => $function::<{ U6144::LIMBS }>($($arg),*),
+            6145..=8192 => $function::<{ U8192::LIMBS }>($($arg),*),
+            _ => None,
+        }
+    };
+}
+
+// base^exp mod modulus, only the lowest exp_bits bits of exp are used
+pub(crate) fn pow(
+    base: &BigUint,
+    exp: &BigUint,
+    exp_bits: u64,
+    modulus: &BigUint,
+) -> Option<BigUint> {
+    with_width!(modulus.bits(), pow_fixed(base, exp, exp_bits, modulus))
+}
+
+// k - c * x mod modulus, for k, c and x below the modulus
+pub(crate) fn sub_mul(k: &BigUint, c: &BigUint, x: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    with_width!(modulus.bits(), sub_mul_fixed(k, c, x, modulus))
+}
+
+// big endian bytes mod modulus
+pub(crate) fn reduce(bytes: &[u8], modulus: &BigUint) -> Option<BigUint> {
+    let bits = modulus.bits().max((bytes.len() as u64 * 8).div_ceil(2));
+    with_width!(bits, reduce_fixed(bytes, modulus))
+}
+
+fn pow_fixed<const LIMBS: usize>(
+    base: &BigUint,
+    exp: &BigUint,
+    exp_bits: u64,
+    modulus: &BigUint,
+) -> Option<BigUint> {
+    let params = residue_params::<LIMBS>(modulus)?;
+    if exp_bits > Uint::<LIMBS>::BITS as u64 {
+        return None;
+    }
+
+    let base = DynResidue::new(&to_uint(&(base % modulus))?, params);
+    let mut exp = to_uint::<LIMBS>(exp)?;
+    let mut result = base.pow_bounded_exp(&exp, exp_bits as usize);
+    exp.zeroize();
+
+    let out = from_uint(&result.retrieve());
+    result.zeroize();
+    Some(out)
+}
+
+fn sub_mul_fixed<const LIMBS: usize>(
+    k: &BigUint,
+    c: &BigUint,
+    x: &BigUint,
+    modulus: &BigUint,
+) -> Option<BigUint> {
+    let params = residue_params::<LIMBS>(modulus)?;
+    let residue = |value: &BigUint| {
+        let mut value = to_uint::<LIMBS>(value)?;
+        let residue = DynResidue::new(&value, params);
+        value.zeroize();
+        Some(residue)
+    };
+
+    // subtracting a residue is a masked add of the modulus, so no branch on k >= c * x
+    let (mut k, c, mut x) = (residue(k)?, residue(c)?, residue(x)?);
+    let mut s = k.sub(&c.mul(&x));
+    k.zeroize();
+    x.zeroize();
+
+    let out = from_uint(&s.retrieve());
+    s.zeroize();
+    Some(out)
+}
+
+fn reduce_fixed<const LIMBS: usize>(bytes: &[u8], modulus: &BigUint) -> Option<BigUint> {
+    let width = Uint::<LIMBS>::BYTES;
+    if bytes.len() > 2 * width {
+        return None;
+    }
+
+    let mut wide = Zeroizing::new(vec![0u8; 2 * width]);
+    wide[2 * width - bytes.len()..].copy_from_slice(bytes);
+    let mut upper = Uint::<LIMBS>::from_be_slice(&wide[..width]);
+    let mut lower = Uint::<LIMBS>::from_be_slice(&wide[width..]);
+
+    // shift and subtract over every bit, variable only in the modulus
+    let (mut remainder, _) = Uint::const_rem_wide((lower, upper), &to_uint(modulus)?);
+    upper.zeroize();
+    lower.zeroize();
+
+    let out = from_uint(&remainder);
+    remainder.zeroize();
+    Some(out)
+}
+
+// Montgomery parameters, None for an even modulus
+fn residue_params<const LIMBS: usize>(modulus: &BigUint) -> Option<DynResidueParams<LIMBS>> {
+    if !modulus.bit(0) {
+        return None;
+    }
+    Some(DynResidueParams::new(&to_uint(modulus)?))
+}
+
+fn to_uint<const LIMBS: usize>(value: &BigUint) -> Option<Uint<LIMBS>> {
+    if value.bits() > Uint::<LIMBS>::BITS as u64 {
+        return None;
+    }
+
+    let value = Zeroizing::new(value.to_bytes_le());
+    let mut bytes = Zeroizing::new(vec![0u8; Uint::<LIMBS>::BYTES]);
+    bytes[..value.len()].copy_from_slice(&value);
+    Some(Uint::from_le_slice(&bytes))
+}
+
+fn from_uint<const LIMBS: usize>(value: &Uint<LIMBS>) -> BigUint {
+    let bytes: Zeroizing<Vec<u8>> = Zeroizing::new(
+        value
+            .as_words()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect(),
+    );
+    BigUint::from_bytes_le(&bytes)
+}
@@ -0,0 +1,65 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::Zeroize;
+
+type HmacSha512 = Hmac<Sha512>;
+
+const OUTLEN: usize = 64;
+
+// HMAC_DRBG from NIST SP 800-90A with SHA-512, the generator RFC 6979 builds on.
+// no reseeding: every instance is seeded once and only used for a single nonce
+pub struct HmacDrbg {
+    k: [u8; OUTLEN],
+    v: [u8; OUTLEN],
+}
+
+impl HmacDrbg {
+    pub fn new(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> Self {
+        let mut drbg = HmacDrbg {
+            k: [0x00; OUTLEN],
+            v: [0x01; OUTLEN],
+        };
+        drbg.update(&[entropy, nonce, personalization]);
+        drbg
+    }
+
+    pub fn generate(&mut self, out: &mut [u8]) {
+        for chunk in out.chunks_mut(OUTLEN) {
+            self.v = self.hmac(&[&self.v]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[]);
+    }
+
+    // K = HMAC(K, V || 0x00 || data), V = HMAC(K, V), repeated with 0x01 if data is not empty
+    fn update(&mut self, data: &[&[u8]]) {
+        let empty = data.iter().all(|part| part.is_empty());
+
+        for separator in [[0x00u8], [0x01]] {
+            let mut input: Vec<&[u8]> = vec![&self.v, &separator];
+            input.extend_from_slice(data);
+
+            self.k = self.hmac(&input);
+            self.v = self.hmac(&[&self.v]);
+
+            if empty {
+                break;
+            }
+        }
+    }
+
+    fn hmac(&self, parts: &[&[u8]]) -> [u8; OUTLEN] {
+        let mut mac = HmacSha512::new_from_slice(&self.k).expect("HMAC accepts any key length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().into()
+    }
+}
+
+impl Drop for HmacDrbg {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.v.zeroize();
+    }
+}
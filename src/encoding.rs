@@ -0,0 +1,35 @@
+use num_bigint::BigUint;
+
+// canonical wire form of modp values: big endian, left padded with zeros to the byte length
+// of the bound (p for elements, q for scalars). the length is fixed per parameter set and
+// every value below the bound has exactly one encoding
+
+// bytes needed to hold any value below bound
+pub fn fixed_length(bound: &BigUint) -> usize {
+    (bound.bits() as usize).div_ceil(8)
+}
+
+// value must be below 2^(8 * length)
+pub fn encode_fixed(value: &BigUint, length: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    assert!(
+        bytes.len() <= length,
+        "value does not fit in {} bytes",
+        length
+    );
+
+    let mut out = vec![0u8; length];
+    out[length - bytes.len()..].copy_from_slice(&bytes);
+    out
+}
+
+// strict inverse of encode_fixed: None unless bytes has exactly the fixed length of bound
+// and holds a value below it, so shorter, longer or unreduced forms are all rejected
+pub fn decode_fixed(bytes: &[u8], bound: &BigUint) -> Option<BigUint> {
+    if bytes.len() != fixed_length(bound) {
+        return None;
+    }
+
+    let value = BigUint::from_bytes_be(bytes);
+    (value < *bound).then_some(value)
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+use crate::params::ParameterError;
+
+// the verification equation that did not hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Equation {
+    // r1 == alpha^s * y1^c
+    R1,
+    // r2 == beta^s * y2^c
+    R2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    // the named element is not in the prime order subgroup
+    InvalidElement(&'static str),
+    // the named scalar is not in [0, q)
+    ScalarOutOfRange(&'static str),
+    InvalidParameters(ParameterError),
+    VerificationFailed(Equation),
+    // the named value could not be decoded from its byte or hex form
+    Encoding(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidElement(name) => write!(f, "{} is not an element of the group", name),
+            Error::ScalarOutOfRange(name) => write!(f, "{} is not a scalar below q", name),
+            Error::InvalidParameters(error) => write!(f, "invalid group parameters: {}", error),
+            Error::VerificationFailed(Equation::R1) => {
+                write!(f, "verification failed: r1 != alpha^s * y1^c")
+            }
+            Error::VerificationFailed(Equation::R2) => {
+                write!(f, "verification failed: r2 != beta^s * y2^c")
+            }
+            Error::Encoding(name) => write!(f, "invalid {} encoding", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidParameters(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParameterError> for Error {
+    fn from(error: ParameterError) -> Self {
+        Error::InvalidParameters(error)
+    }
+}
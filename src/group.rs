@@ -0,0 +1,276 @@
+use num_bigint::{BigUint, RandBigInt};
+use rand_core::CryptoRngCore;
+use std::fmt::Debug;
+
+use crate::multiexp::ModpFixedBase;
+use crate::{ct, encoding};
+
+// extra bytes hashed or derived beyond scalar_length so reducing mod q leaves a negligible bias
+pub(crate) const REDUCTION_MARGIN: usize = 16;
+
+// prime order group the Chaum-Pedersen protocol runs in
+// elements are written multiplicatively: exp(g, x) = g^x, mul(a, b) = a * b
+pub trait Group {
+    type Element: Clone + PartialEq + Debug;
+    type Scalar: Clone + PartialEq + Debug;
+    // precomputed powers of a base that never changes, e.g. alpha and beta
+    type FixedBase;
+
+    // below this many transcripts verify_batch checks them one by one, which is faster.
+    // see the numbers in benches/batch.rs
+    const BATCH_THRESHOLD: usize = 2;
+
+    // unambiguous description of the group, bound into Fiat-Shamir challenges
+    fn parameters(&self) -> Vec<u8>;
+
+    fn identity(&self) -> Self::Element;
+
+    fn exp(&self, base: &Self::Element, exp: &Self::Scalar) -> Self::Element;
+
+    // exp in constant time with respect to exp, for the secret x and the nonce k
+    fn exp_secret(&self, base: &Self::Element, exp: &Self::Scalar) -> Self::Element;
+
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    fn fixed_base(&self, base: &Self::Element) -> Self::FixedBase;
+
+    // same result as exp on the base the table was built from
+    fn exp_fixed_base(&self, table: &Self::FixedBase, exp: &Self::Scalar) -> Self::Element;
+
+    // exp_fixed_base in constant time with respect to exp
+    fn exp_fixed_base_secret(&self, table: &Self::FixedBase, exp: &Self::Scalar) -> Self::Element;
+
+    // product of base^exp over all terms. may run in variable time, only pass public exponents
+    fn multi_exp(&self, terms: &[(&Self::Element, &Self::Scalar)]) -> Self::Element {
+        terms.iter().fold(self.identity(), |product, (base, exp)| {
+            self.mul(&product, &self.exp(base, exp))
+        })
+    }
+
+    // multi_exp with some of the bases given as fixed base tables
+    fn multi_exp_fixed_base(
+        &self,
+        fixed: &[(&Self::FixedBase, &Self::Scalar)],
+        terms: &[(&Self::Element, &Self::Scalar)],
+    ) -> Self::Element {
+        fixed
+            .iter()
+            .fold(self.multi_exp(terms), |product, (table, exp)| {
+                self.mul(&product, &self.exp_fixed_base(table, exp))
+            })
+    }
+
+    // true if the element belongs to the prime order subgroup
+    fn is_member(&self, element: &Self::Element) -> bool;
+
+    // uniform in [0, q)
+    fn random_scalar(&self, rng: &mut dyn CryptoRngCore) -> Self::Scalar;
+
+    fn is_zero(&self, scalar: &Self::Scalar) -> bool;
+
+    // true if the scalar is reduced, i.e. in [0, q)
+    fn is_valid_scalar(&self, scalar: &Self::Scalar) -> bool;
+
+    // bytes needed to hold any scalar below q
+    fn scalar_length(&self) -> usize;
+
+    // a + b mod q
+    fn scalar_add(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    // a - b mod q
+    fn scalar_sub(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    // a * b mod q
+    fn scalar_mul(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    // k - c * x mod q in constant time with respect to k and x, the prover's response
+    fn scalar_sub_mul(&self, k: &Self::Scalar, c: &Self::Scalar, x: &Self::Scalar) -> Self::Scalar;
+
+    // a^-1 mod q, None for zero
+    fn scalar_invert(&self, a: &Self::Scalar) -> Option<Self::Scalar>;
+
+    // value mod q, used for small batching weights
+    fn scalar_from_u128(&self, value: u128) -> Self::Scalar;
+
+    // overwrites the scalar's memory with zeros, used when a secret is dropped
+    fn zeroize_scalar(scalar: &mut Self::Scalar);
+
+    // maps arbitrary bytes (e.g. a password) onto a scalar, in constant time
+    fn scalar_from_bytes(&self, bytes: &[u8]) -> Self::Scalar;
+
+    // byte length of every encoded element
+    fn element_length(&self) -> usize;
+
+    // canonical encodings, element_length and scalar_length bytes long. decoding rejects
+    // any other length and any value that is not the canonical form of itself
+    fn encode_element(&self, element: &Self::Element) -> Vec<u8>;
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<Self::Element>;
+
+    fn encode_scalar(&self, scalar: &Self::Scalar) -> Vec<u8>;
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<Self::Scalar>;
+}
+
+// order q subgroup of the multiplicative group mod p
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModpGroup {
+    p: BigUint, // prime modulus
+    q: BigUint, // order of the group
+}
+
+impl ModpGroup {
+    pub fn new(p: BigUint, q: BigUint) -> Self {
+        ModpGroup { p, q }
+    }
+
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    pub fn q(&self) -> &BigUint {
+        &self.q
+    }
+}
+
+impl Group for ModpGroup {
+    type Element = BigUint;
+    type Scalar = BigUint;
+    type FixedBase = ModpFixedBase;
+
+    // a batch checks r1 and r2 for subgroup membership up front, one by one that only
+    // happens when an equation fails. that eats what combining saves until batches are large
+    const BATCH_THRESHOLD: usize = 64;
+
+    fn parameters(&self) -> Vec<u8> {
+        let mut out = b"modp".to_vec();
+        for value in [&self.p, &self.q] {
+            let bytes = value.to_bytes_be();
+            out.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+            out.extend_from_slice(&bytes);
+        }
+        out
+    }
+
+    fn identity(&self) -> BigUint {
+        BigUint::from(1u32)
+    }
+
+    fn exp(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        base.modpow(exp, &self.p)
+    }
+
+    // falls back to modpow only for moduli the constant time backend does not cover
+    fn exp_secret(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        let exp_bits = self.q.bits().max(exp.bits());
+        ct::pow(base, exp, exp_bits, &self.p).unwrap_or_else(|| self.exp(base, exp))
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.p
+    }
+
+    fn fixed_base(&self, base: &BigUint) -> ModpFixedBase {
+        self.comb_table(base)
+    }
+
+    fn exp_fixed_base(&self, table: &ModpFixedBase, exp: &BigUint) -> BigUint {
+        self.comb_exp(table, exp)
+    }
+
+    // comb lookups are indexed by exponent bits, a secret exponent skips the table
+    fn exp_fixed_base_secret(&self, table: &ModpFixedBase, exp: &BigUint) -> BigUint {
+        self.exp_secret(table.base(), exp)
+    }
+
+    fn multi_exp(&self, terms: &[(&BigUint, &BigUint)]) -> BigUint {
+        self.straus(terms)
+    }
+
+    fn is_member(&self, element: &BigUint) -> bool {
+        *element > BigUint::ZERO
+            && *element < self.p
+            && element.modpow(&self.q, &self.p) == self.identity()
+    }
+
+    fn random_scalar(&self, rng: &mut dyn CryptoRngCore) -> BigUint {
+        rng.gen_biguint_below(&self.q)
+    }
+
+    fn is_zero(&self, scalar: &BigUint) -> bool {
+        *scalar == BigUint::ZERO
+    }
+
+    fn is_valid_scalar(&self, scalar: &BigUint) -> bool {
+        *scalar < self.q
+    }
+
+    fn scalar_length(&self) -> usize {
+        encoding::fixed_length(&self.q)
+    }
+
+    fn scalar_add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + b) % &self.q
+    }
+
+    fn scalar_sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let a = a % &self.q;
+        let b = b % &self.q;
+
+        if a >= b { a - b } else { &self.q - (b - a) }
+    }
+
+    fn scalar_mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.q
+    }
+
+    fn scalar_sub_mul(&self, k: &BigUint, c: &BigUint, x: &BigUint) -> BigUint {
+        ct::sub_mul(k, c, x, &self.q).unwrap_or_else(|| self.scalar_sub(k, &self.scalar_mul(c, x)))
+    }
+
+    // q is prime, so a^(q - 2) is the inverse by Fermat's little theorem
+    fn scalar_invert(&self, a: &BigUint) -> Option<BigUint> {
+        let a = a % &self.q;
+        if a == BigUint::ZERO {
+            return None;
+        }
+        Some(a.modpow(&(&self.q - 2u32), &self.q))
+    }
+
+    fn scalar_from_u128(&self, value: u128) -> BigUint {
+        BigUint::from(value) % &self.q
+    }
+
+    // BigUint has no zeroize support, but clearing its bits from the bottom up
+    // zeroes every limb in place before normalize truncates or reallocates the buffer.
+    // temporaries created inside num-bigint arithmetic are not covered
+    fn zeroize_scalar(scalar: &mut BigUint) {
+        for bit in 0..scalar.bits() {
+            scalar.set_bit(bit, false);
+        }
+    }
+
+    fn scalar_from_bytes(&self, bytes: &[u8]) -> BigUint {
+        ct::reduce(bytes, &self.q).unwrap_or_else(|| BigUint::from_bytes_be(bytes) % &self.q)
+    }
+
+    fn element_length(&self) -> usize {
+        encoding::fixed_length(&self.p)
+    }
+
+    fn encode_element(&self, element: &BigUint) -> Vec<u8> {
+        encoding::encode_fixed(element, self.element_length())
+    }
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<BigUint> {
+        encoding::decode_fixed(bytes, &self.p)
+    }
+
+    fn encode_scalar(&self, scalar: &BigUint) -> Vec<u8> {
+        encoding::encode_fixed(scalar, self.scalar_length())
+    }
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<BigUint> {
+        encoding::decode_fixed(bytes, &self.q)
+    }
+}
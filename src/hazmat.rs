@@ -0,0 +1,19 @@
+use crate::{Error, Group, Nonce, Proof, SecretScalar};
+
+// the pieces ProverSession keeps private, for known answer tests and test vectors.
+// nothing here stops one k from answering two challenges, which gives away x
+// (see Proof::extract_secret), so provers must go through ProverSession instead
+
+pub fn nonce<G: Group>(k: G::Scalar) -> Nonce<G> {
+    Nonce::new(k)
+}
+
+// s = k - c * x mod q
+pub fn solve<G: Group>(
+    proof: &Proof<G>,
+    k: Nonce<G>,
+    c: &G::Scalar,
+    x: &SecretScalar<G>,
+) -> Result<G::Scalar, Error> {
+    proof.solve(k, c, x)
+}
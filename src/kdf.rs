@@ -0,0 +1,224 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use pbkdf2::pbkdf2_hmac;
+use rand_core::{CryptoRngCore, OsRng};
+use sha2::Sha512;
+use std::{fmt, str::FromStr};
+use zeroize::Zeroizing;
+
+use crate::auth::KdfParameters;
+use crate::group::{Group, REDUCTION_MARGIN};
+use crate::secret::{Secret, SecretScalar};
+
+pub const SALT_LENGTH: usize = 16;
+
+// shortest salt accepted from a client, argon2 refuses anything below this
+pub const MIN_SALT_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KdfError {
+    InvalidParameters(String),
+}
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KdfError::InvalidParameters(reason) => write!(f, "invalid kdf parameters: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for KdfError {}
+
+// password hashing function and its cost parameters, stored next to the salt at registration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+    },
+    Pbkdf2Sha512 {
+        iterations: u32,
+    },
+}
+
+// OWASP minimum recommendation for Argon2id
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf::Argon2id {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl FromStr for Kdf {
+    type Err = String;
+
+    // picks an algorithm by name with its recommended cost parameters
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "argon2id" => Ok(Kdf::default()),
+            "scrypt" => Ok(Kdf::Scrypt {
+                log_n: 17,
+                r: 8,
+                p: 1,
+            }),
+            "pbkdf2" => Ok(Kdf::Pbkdf2Sha512 {
+                iterations: 210_000,
+            }),
+            _ => Err(format!(
+                "unknown kdf: {} (expected argon2id, scrypt or pbkdf2)",
+                s
+            )),
+        }
+    }
+}
+
+impl Kdf {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kdf::Argon2id { .. } => "argon2id",
+            Kdf::Scrypt { .. } => "scrypt",
+            Kdf::Pbkdf2Sha512 { .. } => "pbkdf2",
+        }
+    }
+
+    // fills out with key material derived from password and salt
+    pub fn derive_bytes(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), KdfError> {
+        let invalid = |error: &dyn fmt::Display| KdfError::InvalidParameters(error.to_string());
+
+        match *self {
+            Kdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                let params = Params::new(memory_kib, iterations, parallelism, Some(out.len()))
+                    .map_err(|e| invalid(&e))?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password, salt, out)
+                    .map_err(|e| invalid(&e))
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                let params =
+                    scrypt::Params::new(log_n, r, p, out.len()).map_err(|e| invalid(&e))?;
+                scrypt::scrypt(password, salt, &params, out).map_err(|e| invalid(&e))
+            }
+            Kdf::Pbkdf2Sha512 { iterations } => {
+                if iterations == 0 {
+                    return Err(KdfError::InvalidParameters(
+                        "pbkdf2 needs at least one iteration".to_string(),
+                    ));
+                }
+                pbkdf2_hmac::<Sha512>(password, salt, iterations, out);
+                Ok(())
+            }
+        }
+    }
+
+    // the wire form, shared by the prover and the verifier
+    pub fn to_proto(&self, salt: &[u8]) -> KdfParameters {
+        let mut parameters = KdfParameters {
+            algorithm: self.name().to_string(),
+            salt: salt.to_vec(),
+            ..Default::default()
+        };
+
+        match *self {
+            Kdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                parameters.memory_kib = memory_kib;
+                parameters.iterations = iterations;
+                parameters.parallelism = parallelism;
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                parameters.log_n = log_n.into();
+                parameters.block_size = r;
+                parameters.parallelism = p;
+            }
+            Kdf::Pbkdf2Sha512 { iterations } => {
+                parameters.iterations = iterations;
+            }
+        }
+
+        parameters
+    }
+
+    // parameters come from the other side, so nothing here may panic. costs are checked
+    // when the kdf runs
+    pub fn from_proto(parameters: KdfParameters) -> Result<(Kdf, Vec<u8>), KdfError> {
+        let invalid = |reason: String| KdfError::InvalidParameters(reason);
+
+        if parameters.salt.len() < MIN_SALT_LENGTH {
+            return Err(invalid("salt is too short".to_string()));
+        }
+
+        let kdf = match parameters.algorithm.as_str() {
+            "argon2id" => Kdf::Argon2id {
+                memory_kib: parameters.memory_kib,
+                iterations: parameters.iterations,
+                parallelism: parameters.parallelism,
+            },
+            "scrypt" => Kdf::Scrypt {
+                log_n: u8::try_from(parameters.log_n)
+                    .map_err(|_| invalid(format!("scrypt log_n {}", parameters.log_n)))?,
+                r: parameters.block_size,
+                p: parameters.parallelism,
+            },
+            "pbkdf2" => Kdf::Pbkdf2Sha512 {
+                iterations: parameters.iterations,
+            },
+            other => return Err(invalid(format!("unknown kdf {:?}", other))),
+        };
+
+        Ok((kdf, parameters.salt))
+    }
+}
+
+pub fn generate_salt() -> Vec<u8> {
+    generate_salt_with_rng(&mut OsRng)
+}
+
+pub fn generate_salt_with_rng(rng: &mut dyn CryptoRngCore) -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_LENGTH];
+    rng.fill_bytes(&mut salt);
+    salt
+}
+
+// derives the secret x in [1, q) from a password and a per-user salt
+pub fn derive_secret<G: Group>(
+    group: &G,
+    kdf: &Kdf,
+    password: &[u8],
+    salt: &[u8],
+) -> Result<SecretScalar<G>, KdfError> {
+    let mut okm = Zeroizing::new(vec![0u8; group.scalar_length() + REDUCTION_MARGIN]);
+    let mut salt = salt.to_vec();
+
+    loop {
+        kdf.derive_bytes(password, &salt, &mut okm)?;
+
+        let x = SecretScalar::new(group.scalar_from_bytes(&okm));
+        if !group.is_zero(x.expose_secret()) {
+            return Ok(x);
+        }
+
+        // x = 0 would make y1 = 1, retry deterministically with an extended salt
+        salt.push(0);
+    }
+}
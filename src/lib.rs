@@ -1,91 +1,586 @@
 use num_bigint::{BigUint, RandBigInt};
-use rand::{self, Rng};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand_core::{CryptoRngCore, OsRng};
+use sha2::{Digest, Sha512};
+use std::str::FromStr;
 
-pub struct Proof {
-    p: BigUint,     // prime modulus
-    q: BigUint,     // order of the group
-    alpha: BigUint, // generator of the group
-    beta: BigUint,  // generator of the group
+#[cfg(feature = "serde")]
+pub mod archive;
+// messages and services of proto/auth.proto, generated by build.rs
+#[rustfmt::skip]
+pub mod auth;
+pub mod batch;
+mod ct;
+pub mod drbg;
+pub mod encoding;
+pub mod error;
+pub mod group;
+#[cfg(feature = "hazmat")]
+pub mod hazmat;
+pub mod kdf;
+pub mod multiexp;
+pub mod parameter_sets;
+pub mod params;
+pub mod ristretto;
+pub mod schnorr;
+pub mod secret;
+pub mod store;
+
+pub use batch::Transcript;
+pub use error::{Equation, Error};
+pub use group::{Group, ModpGroup};
+pub use kdf::{Kdf, KdfError};
+pub use parameter_sets::{DEFAULT_PARAMETER_SET, ParameterSet};
+pub use params::{MinimumStrength, ParameterError};
+pub use ristretto::Ristretto255;
+pub use schnorr::{Schnorr, SchnorrProof};
+pub use secret::{Nonce, Secret, SecretScalar};
+
+// group backend used by the prover and the verifier, both sides must agree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Modp,
+    Ristretto,
 }
 
-impl Proof {
-    pub fn new(p: BigUint, q: BigUint, alpha: BigUint, beta: BigUint) -> Self {
-        Proof { p, q, alpha, beta }
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "modp" => Ok(Backend::Modp),
+            "ristretto" => Ok(Backend::Ristretto),
+            _ => Err(format!(
+                "unknown backend: {} (expected modp or ristretto)",
+                s
+            )),
+        }
+    }
+}
+
+impl Backend {
+    // reads the backend from the first positional command line argument, defaults to modp
+    pub fn from_args() -> Self {
+        std::env::args()
+            .skip(1)
+            .find(|arg| !arg.starts_with("--"))
+            .map(|arg| arg.parse().expect("Could not parse the backend"))
+            .unwrap_or_default()
+    }
+}
+
+// randomness source shared by the binaries
+pub type BoxedRng = Box<dyn CryptoRngCore + Send>;
+
+// reads --seed=<u64> from the command line for a reproducible prover run, defaults to the OS
+// RNG. a seeded RNG is predictable by anyone who knows the seed, never use it in production.
+// the verifier does not read it
+pub fn rng_from_args() -> BoxedRng {
+    match std::env::args().find_map(|arg| arg.strip_prefix("--seed=").map(str::to_string)) {
+        Some(seed) => {
+            let seed = seed.parse().expect("Could not parse the seed");
+            Box::new(StdRng::seed_from_u64(seed))
+        }
+        None => Box::new(OsRng),
+    }
+}
+
+// seed beta is derived from in get_constants, see ModpGroup::derive_generator
+pub const BETA_SEED: &[u8] = b"cp-protocol beta";
+
+// domain separation tag for Fiat-Shamir challenges
+const FIAT_SHAMIR_TAG: &[u8] = b"cp-protocol fiat-shamir v1";
+
+// domain separation tag for the context deterministic login nonces are derived from
+const NONCE_CONTEXT_TAG: &[u8] = b"cp-protocol nonce context v1";
+
+// commitment and response of a proof whose challenge is derived by hashing
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonInteractiveProof<G: Group> {
+    pub r1: G::Element,
+    pub r2: G::Element,
+    pub s: G::Scalar,
+}
+
+// prover side of the interactive protocol: commit() draws a fresh k and returns
+// a Commitment, whose respond() consumes it, so one k can never answer two challenges
+pub struct ProverSession<'a, G: Group> {
+    proof: &'a Proof<G>,
+    x: &'a SecretScalar<G>,
+}
+
+// one-time state between sending (r1, r2) and answering the challenge
+pub struct Commitment<'a, G: Group> {
+    proof: &'a Proof<G>,
+    x: &'a SecretScalar<G>,
+    k: Nonce<G>,
+    r1: G::Element,
+    r2: G::Element,
+}
+
+impl<'a, G: Group> ProverSession<'a, G> {
+    pub fn commit(&self) -> Result<Commitment<'a, G>, Error> {
+        self.commit_with_rng(&mut OsRng)
+    }
+
+    pub fn commit_with_rng(&self, rng: &mut dyn CryptoRngCore) -> Result<Commitment<'a, G>, Error> {
+        self.commit_with(self.proof.generate_nonce_with_rng(rng))
+    }
+
+    // k derived from x and context instead of the RNG, see Nonce::derive.
+    // a verifier that sees the same commitment twice can ask two challenges and
+    // recover x, so this consumes the session and context has to be unique per session
+    // (e.g. a counter or timestamp). extra_entropy keeps k fresh if the context repeats
+    pub fn commit_deterministic(
+        self,
+        context: &[u8],
+        extra_entropy: Option<&[u8]>,
+    ) -> Result<Commitment<'a, G>, Error> {
+        let k = Nonce::derive(self.proof.group(), self.x, context, extra_entropy);
+        self.commit_with(k)
+    }
+
+    fn commit_with(&self, k: Nonce<G>) -> Result<Commitment<'a, G>, Error> {
+        let [r1, r2] = self.proof.create_pair(&k)?;
+
+        Ok(Commitment {
+            proof: self.proof,
+            x: self.x,
+            k,
+            r1,
+            r2,
+        })
+    }
+}
+
+impl<G: Group> Commitment<'_, G> {
+    pub fn r1(&self) -> &G::Element {
+        &self.r1
+    }
+
+    pub fn r2(&self) -> &G::Element {
+        &self.r2
+    }
+
+    // s = k - c * x mod q
+    pub fn respond(self, c: &G::Scalar) -> Result<G::Scalar, Error> {
+        self.proof.solve(self.k, c, self.x)
+    }
+}
+
+// index into the fixed base tables
+#[derive(Debug, Clone, Copy)]
+enum Generator {
+    Alpha = 0,
+    Beta = 1,
+}
+
+pub struct Proof<G: Group = ModpGroup> {
+    group: G,
+    alpha: G::Element, // generator of the group
+    beta: G::Element,  // generator of the group
+    // fixed base tables for alpha and beta, see with_fixed_base_tables
+    tables: Option<[G::FixedBase; 2]>,
+}
+
+impl<G: Group> Proof<G> {
+    pub fn with_group(group: G, alpha: G::Element, beta: G::Element) -> Self {
+        Proof {
+            group,
+            alpha,
+            beta,
+            tables: None,
+        }
+    }
+
+    // precomputes powers of alpha and beta once, trading memory (about 64 KiB per
+    // generator for a 2048 bit modp group) for a faster verify. create_pair only uses
+    // tables that are constant time to read, i.e. on ristretto255
+    pub fn with_fixed_base_tables(mut self) -> Self {
+        self.tables = Some([
+            self.group.fixed_base(&self.alpha),
+            self.group.fixed_base(&self.beta),
+        ]);
+        self
+    }
+
+    pub fn group(&self) -> &G {
+        &self.group
     }
 
     // r1 == alpha^s * y1^c
     // r2 == beta^s * y2^c
     pub fn verify(
         &self,
-        r1: &BigUint,
-        r2: &BigUint,
-        y1: &BigUint,
-        y2: &BigUint,
-        c: &BigUint,
-        s: &BigUint,
-    ) -> bool {
-        let lhs1 = (&self.alpha.modpow(s, &self.p) * y1.modpow(c, &self.p))
-            .modpow(&BigUint::from(1u32), &self.p);
-        let lhs2 = (&self.beta.modpow(s, &self.p) * y2.modpow(c, &self.p))
-            .modpow(&BigUint::from(1u32), &self.p);
-
-        lhs1 == *r1 && lhs2 == *r2
-    }
-
-    // s = k - c * x
-    // k cant be less then c * x
-    pub fn solve(&self, k: &BigUint, c: &BigUint, x: &BigUint) -> BigUint {
-        if *k >= c * x {
-            return (k - c * x).modpow(&BigUint::from(1u32), &self.q);
+        r1: &G::Element,
+        r2: &G::Element,
+        y1: &G::Element,
+        y2: &G::Element,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<(), Error> {
+        // an element outside the subgroup could leak x mod a small cofactor.
+        // a commitment equal to a product of members is a member itself, so r1 and r2
+        // only need their own check to tell a malformed commitment from a wrong one
+        self.check_element("y1", y1)?;
+        self.check_element("y2", y2)?;
+        self.check_scalar("c", c)?;
+        self.check_scalar("s", s)?;
+
+        if let Err(equation) = self.check_equations(r1, r2, y1, y2, c, s) {
+            match equation {
+                Equation::R1 => self.check_element("r1", r1)?,
+                Equation::R2 => self.check_element("r2", r2)?,
+            }
+            return Err(Error::VerificationFailed(equation));
         }
 
-        &self.q - (c * x - k).modpow(&BigUint::from(1u32), &self.q)
+        Ok(())
     }
 
-    pub fn create_pair(&self, exp: &BigUint) -> [BigUint; 2] {
-        [
-            self.alpha.modpow(exp, &self.p),
-            self.beta.modpow(exp, &self.p),
-        ]
+    // the two verification equations alone, without any membership or range checks
+    fn check_equations(
+        &self,
+        r1: &G::Element,
+        r2: &G::Element,
+        y1: &G::Element,
+        y2: &G::Element,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<(), Equation> {
+        if self.multi_exp_generators(&[(Generator::Alpha, s)], &[(y1, c)]) != *r1 {
+            return Err(Equation::R1);
+        }
+        if self.multi_exp_generators(&[(Generator::Beta, s)], &[(y2, c)]) != *r2 {
+            return Err(Equation::R2);
+        }
+        Ok(())
     }
 
-    pub fn generate_random_number_below(limit: &BigUint) -> BigUint {
-        let mut rng = rand::thread_rng();
-        rng.gen_biguint_below(limit)
+    fn generator(&self, generator: Generator) -> (&G::Element, Option<&G::FixedBase>) {
+        let index = generator as usize;
+        let table = self.tables.as_ref().map(|tables| &tables[index]);
+        match generator {
+            Generator::Alpha => (&self.alpha, table),
+            Generator::Beta => (&self.beta, table),
+        }
     }
 
-    pub fn generate_random_string_below(size: usize) -> String {
-        let rng = rand::thread_rng();
-        rng.sample_iter(rand::distributions::Alphanumeric)
-            .take(size)
-            .map(char::from)
-            .collect()
+    // generator^exp for a secret exponent, in constant time
+    fn exp_generator_secret(&self, generator: Generator, exp: &G::Scalar) -> G::Element {
+        match self.generator(generator) {
+            (_, Some(table)) => self.group.exp_fixed_base_secret(table, exp),
+            (base, None) => self.group.exp_secret(base, exp),
+        }
+    }
+
+    // product of generator^exp and base^exp over both lists, for public exponents only
+    fn multi_exp_generators(
+        &self,
+        generators: &[(Generator, &G::Scalar)],
+        terms: &[(&G::Element, &G::Scalar)],
+    ) -> G::Element {
+        let group = &self.group;
+        match &self.tables {
+            Some(tables) => {
+                let fixed: Vec<_> = generators
+                    .iter()
+                    .map(|(generator, exp)| (&tables[*generator as usize], *exp))
+                    .collect();
+                group.multi_exp_fixed_base(&fixed, terms)
+            }
+            None => {
+                let mut all: Vec<_> = generators
+                    .iter()
+                    .map(|(generator, exp)| (self.generator(*generator).0, *exp))
+                    .collect();
+                all.extend_from_slice(terms);
+                group.multi_exp(&all)
+            }
+        }
     }
 
-    pub fn generate_random_number(&self) -> BigUint {
-        Proof::generate_random_number_below(&self.q)
+    // s = k - c * x mod q, k is consumed so it cannot answer a second challenge. only
+    // Commitment::respond reaches this outside the hazmat feature
+    pub(crate) fn solve(
+        &self,
+        k: Nonce<G>,
+        c: &G::Scalar,
+        x: &SecretScalar<G>,
+    ) -> Result<G::Scalar, Error> {
+        let (k, x) = (k.expose_secret(), x.expose_secret());
+
+        self.check_scalar("k", k)?;
+        self.check_scalar("c", c)?;
+        self.check_scalar("x", x)?;
+
+        Ok(self.group.scalar_sub_mul(k, c, x))
     }
 
-    pub fn get_constants() -> (BigUint, BigUint, BigUint, BigUint) {
-        let p = BigUint::from_bytes_be(&hex::decode(
-            "AD107E1E9123A9D0D660FAA79559C51FA20D64E5683B9FD1B54B1597B61D0A75E6FA141DF95A56DBAF9A3C407BA1DF15EB3D688A309C180E1DE6B85A1274A0A66D3F8152AD6AC2129037C9EDEFDA4DF8D91E8FEF55B7394B7AD5B7D0B6C12207C9F98D11ED34DBF6C6BA0B2C8BBC27BE6A00E0A0B9C49708B3BF8A317091883681286130BC8985DB1602E714415D9330278273C7DE31EFDC7310F7121FD5A07415987D9ADC0A486DCDF93ACC44328387315D75E198C641A480CD86A1B9E587E8BE60E69CC928B2B9C52172E413042E9B23F10B0E16E79763C9B53DCF4BA80A29E3FB73C16B8E75B97EF363E2FFA31F71CF9DE5384E71B81C0AC4DFFE0C10E64F",
-        ).unwrap());
+    // (alpha^e, beta^e) for the secret x at registration or the nonce k when committing
+    pub fn create_pair<S: Secret<G>>(&self, secret: &S) -> Result<[G::Element; 2], Error> {
+        let exp = secret.expose_secret();
+        self.check_scalar("exponent", exp)?;
 
-        let alpha = BigUint::from_bytes_be(&hex::decode(
-            "AC4032EF4F2D9AE39DF30B5C8FFDAC506CDEBE7B89998CAF74866A08CFE4FFE3A6824A4E10B9A6F0DD921F01A70C4AFAAB739D7700C29F52C57DB17C620A8652BE5E9001A8D66AD7C17669101999024AF4D027275AC1348BB8A762D0521BC98AE247150422EA1ED409939D54DA7460CDB5F6C6B250717CBEF180EB34118E98D119529A45D6F834566E3025E316A330EFBB77A86F0C1AB15B051AE3D428C8F8ACB70A8137150B8EEB10E183EDD19963DDD9E263E4770589EF6AA21E7F5F2FF381B539CCE3409D13CD566AFBB48D6C019181E1BCFE94B30269EDFE72FE9B6AA4BD7B5A0F1C71CFFF4C19C418E1F6EC017981BC087F2A7065B384B890D3191F2BFA",
-        ).unwrap());
+        Ok([
+            self.exp_generator_secret(Generator::Alpha, exp),
+            self.exp_generator_secret(Generator::Beta, exp),
+        ])
+    }
 
-        let exp = BigUint::from_bytes_be(&hex::decode("65B384B890D3191F2BFA").unwrap());
+    // two valid transcripts (c1, s1), (c2, s2) answering the same commitment give away x:
+    // s1 - s2 = (c2 - c1) * x, so x = (s1 - s2) / (c2 - c1). None if c1 == c2
+    pub fn extract_secret(
+        &self,
+        c1: &G::Scalar,
+        s1: &G::Scalar,
+        c2: &G::Scalar,
+        s2: &G::Scalar,
+    ) -> Option<SecretScalar<G>> {
+        let group = &self.group;
+        let inverse = group.scalar_invert(&group.scalar_sub(c2, c1))?;
 
-        // q is the order of the group, it must be a prime number
-        let q = BigUint::from_bytes_be(
-            &hex::decode("801C0D34C58D93FE997177101F80535A4738CEBCBF389A99B36371EB").unwrap(),
+        Some(SecretScalar::new(
+            group.scalar_mul(&group.scalar_sub(s1, s2), &inverse),
+        ))
+    }
+
+    pub fn decode_element(&self, name: &'static str, bytes: &[u8]) -> Result<G::Element, Error> {
+        let element = self
+            .group
+            .decode_element(bytes)
+            .ok_or(Error::Encoding(name))?;
+        self.check_element(name, &element)?;
+        Ok(element)
+    }
+
+    pub fn decode_scalar(&self, name: &'static str, bytes: &[u8]) -> Result<G::Scalar, Error> {
+        let scalar = self
+            .group
+            .decode_scalar(bytes)
+            .ok_or(Error::Encoding(name))?;
+        self.check_scalar(name, &scalar)?;
+        Ok(scalar)
+    }
+
+    fn check_element(&self, name: &'static str, element: &G::Element) -> Result<(), Error> {
+        if !self.group.is_member(element) {
+            return Err(Error::InvalidElement(name));
+        }
+        Ok(())
+    }
+
+    fn check_scalar(&self, name: &'static str, scalar: &G::Scalar) -> Result<(), Error> {
+        if !self.group.is_valid_scalar(scalar) {
+            return Err(Error::ScalarOutOfRange(name));
+        }
+        Ok(())
+    }
+
+    // randomness defaults to the operating system RNG, the _with_rng variants take
+    // any cryptographic RNG, e.g. a seeded one for reproducible simulations
+    pub fn generate_random_number(&self) -> G::Scalar {
+        self.generate_random_number_with_rng(&mut OsRng)
+    }
+
+    pub fn generate_random_number_with_rng(&self, rng: &mut dyn CryptoRngCore) -> G::Scalar {
+        self.group.random_scalar(rng)
+    }
+
+    pub fn generate_nonce(&self) -> Nonce<G> {
+        self.generate_nonce_with_rng(&mut OsRng)
+    }
+
+    pub fn generate_nonce_with_rng(&self, rng: &mut dyn CryptoRngCore) -> Nonce<G> {
+        Nonce::random(&self.group, rng)
+    }
+
+    // starts proving knowledge of x, see ProverSession
+    pub fn prover<'a>(&'a self, x: &'a SecretScalar<G>) -> ProverSession<'a, G> {
+        ProverSession { proof: self, x }
+    }
+
+    // c = H(params, alpha, beta, y1, y2, r1, r2, identifier, nonce) mod q
+    pub fn challenge(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        r1: &G::Element,
+        r2: &G::Element,
+        identifier: &str,
+        nonce: &[u8],
+    ) -> G::Scalar {
+        let group = &self.group;
+
+        let digest = self.hash_transcript(
+            FIAT_SHAMIR_TAG,
+            [
+                group.encode_element(y1),
+                group.encode_element(y2),
+                group.encode_element(r1),
+                group.encode_element(r2),
+                identifier.as_bytes().to_vec(),
+                nonce.to_vec(),
+            ],
+        );
+
+        group.scalar_from_bytes(&digest)
+    }
+
+    // proves knowledge of x for y1 = alpha^x, y2 = beta^x without a verifier round trip
+    pub fn prove_non_interactive(
+        &self,
+        x: &SecretScalar<G>,
+        identifier: &str,
+        nonce: &[u8],
+    ) -> Result<NonInteractiveProof<G>, Error> {
+        self.prove_non_interactive_with_rng(x, identifier, nonce, &mut OsRng)
+    }
+
+    pub fn prove_non_interactive_with_rng(
+        &self,
+        x: &SecretScalar<G>,
+        identifier: &str,
+        nonce: &[u8],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<NonInteractiveProof<G>, Error> {
+        let y = self.create_pair(x)?;
+        self.prove_with_commitment(x, identifier, nonce, y, |session| {
+            session.commit_with_rng(rng)
+        })
+    }
+
+    // like prove_non_interactive, but k is derived from x and everything the challenge
+    // hashes except the commitment. proving the same statement twice gives the same
+    // proof, a new identifier or nonce gives a fresh k
+    pub fn prove_non_interactive_deterministic(
+        &self,
+        x: &SecretScalar<G>,
+        identifier: &str,
+        nonce: &[u8],
+        extra_entropy: Option<&[u8]>,
+    ) -> Result<NonInteractiveProof<G>, Error> {
+        let group = &self.group;
+        let [y1, y2] = self.create_pair(x)?;
+
+        let context = self.hash_transcript(
+            NONCE_CONTEXT_TAG,
+            [
+                group.encode_element(&y1),
+                group.encode_element(&y2),
+                identifier.as_bytes().to_vec(),
+                nonce.to_vec(),
+            ],
         );
 
-        // alpha^x is also a generator of the group
-        // any element of the group elevated to x is also a generator
-        let beta = alpha.modpow(&exp, &p);
+        self.prove_with_commitment(x, identifier, nonce, [y1, y2], |session| {
+            session.commit_deterministic(&context, extra_entropy)
+        })
+    }
+
+    fn prove_with_commitment(
+        &self,
+        x: &SecretScalar<G>,
+        identifier: &str,
+        nonce: &[u8],
+        [y1, y2]: [G::Element; 2],
+        commit: impl for<'a> FnOnce(ProverSession<'a, G>) -> Result<Commitment<'a, G>, Error>,
+    ) -> Result<NonInteractiveProof<G>, Error> {
+        let commitment = commit(self.prover(x))?;
+        let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+
+        let c = self.challenge(&y1, &y2, &r1, &r2, identifier, nonce);
+        let s = commitment.respond(&c)?;
+
+        Ok(NonInteractiveProof { r1, r2, s })
+    }
+
+    // H(tag, params, alpha, beta, fields...) with every field length prefixed,
+    // so no two transcripts hash the same bytes
+    fn hash_transcript<const N: usize>(&self, tag: &[u8], fields: [Vec<u8>; N]) -> Vec<u8> {
+        let group = &self.group;
+        let mut hasher = Sha512::new();
+        hasher.update(tag);
+
+        let common = [
+            group.parameters(),
+            group.encode_element(&self.alpha),
+            group.encode_element(&self.beta),
+        ];
+
+        for field in common.iter().chain(fields.iter()) {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
+
+        hasher.finalize().to_vec()
+    }
+
+    pub fn verify_non_interactive(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        identifier: &str,
+        nonce: &[u8],
+        proof: &NonInteractiveProof<G>,
+    ) -> Result<(), Error> {
+        let c = self.challenge(y1, y2, &proof.r1, &proof.r2, identifier, nonce);
+        self.verify(&proof.r1, &proof.r2, y1, y2, &c, &proof.s)
+    }
+}
+
+impl Proof {
+    pub fn new(p: BigUint, q: BigUint, alpha: BigUint, beta: BigUint) -> Self {
+        Proof::with_group(ModpGroup::new(p, q), alpha, beta)
+    }
+
+    // like new, but refuses parameters that would make the proof unsound
+    pub fn try_new(
+        p: BigUint,
+        q: BigUint,
+        alpha: BigUint,
+        beta: BigUint,
+        minimum: &MinimumStrength,
+    ) -> Result<Self, Error> {
+        let group = ModpGroup::new(p, q);
+
+        group.validate(minimum)?;
+        group.validate_generator("alpha", &alpha)?;
+        group.validate_generator("beta", &beta)?;
+
+        Ok(Proof::with_group(group, alpha, beta))
+    }
+
+    pub fn generate_random_number_below(limit: &BigUint) -> BigUint {
+        Proof::generate_random_number_below_with_rng(limit, &mut OsRng)
+    }
+
+    pub fn generate_random_number_below_with_rng(
+        limit: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+    ) -> BigUint {
+        rng.gen_biguint_below(limit)
+    }
+
+    pub fn generate_random_string_below(size: usize) -> String {
+        Proof::generate_random_string_below_with_rng(size, &mut OsRng)
+    }
+
+    pub fn generate_random_string_below_with_rng(
+        size: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> String {
+        rng.sample_iter(rand::distributions::Alphanumeric)
+            .take(size)
+            .map(char::from)
+            .collect()
+    }
 
-        (p, q, alpha, beta)
+    // the RFC 5114 2048-bit group with a 224-bit subgroup, see parameter_sets
+    pub fn get_constants() -> Result<(BigUint, BigUint, BigUint, BigUint), Error> {
+        ParameterSet::by_name(DEFAULT_PARAMETER_SET)
+            .expect("default parameter set is registered")
+            .constants()
     }
 }
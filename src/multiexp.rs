@@ -0,0 +1,116 @@
+use num_bigint::BigUint;
+
+use crate::group::{Group, ModpGroup};
+
+// bits of the exponent read per comb step, the table holds 2^COMB_TEETH entries
+const COMB_TEETH: usize = 8;
+
+// window width of the interleaved multi-exponentiation, 2^STRAUS_WINDOW powers per base
+const STRAUS_WINDOW: u8 = 4;
+
+// comb table for a base that never changes: entry i is the product of base^(2^(j * spacing))
+// over the bits j set in i, so one lookup covers COMB_TEETH bits spread across the exponent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModpFixedBase {
+    base: BigUint,
+    spacing: u64,
+    table: Vec<BigUint>,
+}
+
+impl ModpFixedBase {
+    pub(crate) fn base(&self) -> &BigUint {
+        &self.base
+    }
+}
+
+impl ModpGroup {
+    pub(crate) fn comb_table(&self, base: &BigUint) -> ModpFixedBase {
+        let spacing = self.q().bits().div_ceil(COMB_TEETH as u64).max(1);
+
+        // base^(2^(j * spacing)) for every tooth j
+        let mut teeth = Vec::with_capacity(COMB_TEETH);
+        let mut power = base % self.p();
+        for _ in 0..COMB_TEETH {
+            teeth.push(power.clone());
+            for _ in 0..spacing {
+                power = self.mul(&power, &power);
+            }
+        }
+
+        let mut table = vec![self.identity(); 1 << COMB_TEETH];
+        for index in 1..table.len() {
+            let lowest = index.trailing_zeros() as usize;
+            table[index] = self.mul(&table[index & (index - 1)], &teeth[lowest]);
+        }
+
+        ModpFixedBase {
+            base: base.clone(),
+            spacing,
+            table,
+        }
+    }
+
+    // spacing squarings and at most spacing multiplications, against about
+    // q.bits() squarings for a plain modpow. the table lookups depend on the
+    // exponent, so this is no more constant time than modpow itself
+    pub(crate) fn comb_exp(&self, fixed: &ModpFixedBase, exp: &BigUint) -> BigUint {
+        // the table only covers exponents below 2^(COMB_TEETH * spacing)
+        if exp.bits() > COMB_TEETH as u64 * fixed.spacing {
+            return fixed.base.modpow(exp, self.p());
+        }
+
+        let mut result = self.identity();
+        for column in (0..fixed.spacing).rev() {
+            result = self.mul(&result, &result);
+
+            let index = (0..COMB_TEETH)
+                .filter(|tooth| exp.bit(*tooth as u64 * fixed.spacing + column))
+                .fold(0, |index, tooth| index | (1 << tooth));
+            if index != 0 {
+                result = self.mul(&result, &fixed.table[index]);
+            }
+        }
+
+        result
+    }
+
+    // Straus: every base shares the same squarings and costs one multiplication per window
+    pub(crate) fn straus(&self, terms: &[(&BigUint, &BigUint)]) -> BigUint {
+        if let [(base, exp)] = terms {
+            return base.modpow(exp, self.p());
+        }
+
+        let powers: Vec<Vec<BigUint>> = terms
+            .iter()
+            .map(|(base, _)| {
+                let mut powers = vec![self.identity(), *base % self.p()];
+                for _ in 2..1 << STRAUS_WINDOW {
+                    powers.push(self.mul(&powers[powers.len() - 1], &powers[1]));
+                }
+                powers
+            })
+            .collect();
+        let digits: Vec<Vec<u8>> = terms
+            .iter()
+            .map(|(_, exp)| exp.to_radix_le(1 << STRAUS_WINDOW))
+            .collect();
+
+        let windows = digits.iter().map(Vec::len).max().unwrap_or(0);
+        let mut result = self.identity();
+        for window in (0..windows).rev() {
+            for _ in 0..STRAUS_WINDOW {
+                result = self.mul(&result, &result);
+            }
+            for (powers, digits) in powers.iter().zip(&digits) {
+                match digits.get(window) {
+                    Some(&digit) if digit != 0 => {
+                        result = self.mul(&result, &powers[digit as usize])
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,219 @@
+use num_bigint::{BigUint, RandBigInt};
+use rand_core::OsRng;
+use sha2::{Digest, Sha512};
+use std::fmt;
+
+use crate::group::{Group, ModpGroup};
+
+// witnesses tried per primality test, a composite survives with probability below 4^-rounds
+const MILLER_RABIN_ROUNDS: usize = 40;
+
+// domain separation tag for hash-to-group generator derivation
+const GENERATOR_TAG: &[u8] = b"cp-protocol generator v1";
+
+const SMALL_PRIMES: [u32; 24] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterError {
+    // p is not a probable prime
+    CompositeModulus,
+    // q is not a probable prime
+    CompositeOrder,
+    // q does not divide p - 1, so there is no subgroup of order q
+    OrderDoesNotDivide,
+    // a generator is 1, out of range or does not have order exactly q
+    InvalidGenerator(&'static str),
+    ModulusTooSmall { bits: u64, minimum: u64 },
+    OrderTooSmall { bits: u64, minimum: u64 },
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::CompositeModulus => write!(f, "p is not prime"),
+            ParameterError::CompositeOrder => write!(f, "q is not prime"),
+            ParameterError::OrderDoesNotDivide => write!(f, "q does not divide p - 1"),
+            ParameterError::InvalidGenerator(name) => {
+                write!(f, "{} is not a generator of the order q subgroup", name)
+            }
+            ParameterError::ModulusTooSmall { bits, minimum } => {
+                write!(f, "p has {} bits, at least {} are required", bits, minimum)
+            }
+            ParameterError::OrderTooSmall { bits, minimum } => {
+                write!(f, "q has {} bits, at least {} are required", bits, minimum)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+// smallest parameter sizes accepted when validating a group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinimumStrength {
+    pub p_bits: u64,
+    pub q_bits: u64,
+}
+
+// 2048 bit modulus with a 224 bit subgroup, roughly 112 bits of security
+impl Default for MinimumStrength {
+    fn default() -> Self {
+        MinimumStrength {
+            p_bits: 2048,
+            q_bits: 224,
+        }
+    }
+}
+
+impl MinimumStrength {
+    // accepts any size, only meant for toy groups in tests
+    pub fn none() -> Self {
+        MinimumStrength {
+            p_bits: 0,
+            q_bits: 0,
+        }
+    }
+}
+
+// Miller-Rabin with random witnesses
+pub fn is_probable_prime(n: &BigUint) -> bool {
+    let one = BigUint::from(1u32);
+    let two = BigUint::from(2u32);
+
+    if *n < two {
+        return false;
+    }
+
+    for prime in SMALL_PRIMES {
+        let prime = BigUint::from(prime);
+        if *n == prime {
+            return true;
+        }
+        if (n % &prime) == BigUint::ZERO {
+            return false;
+        }
+    }
+
+    // n - 1 = d * 2^r with d odd
+    let n_minus_one = n - &one;
+    let r = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> r;
+
+    // witnesses only need to be unpredictable to whoever chose n
+    let mut rng = OsRng;
+    'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+
+        if x == one || x == n_minus_one {
+            continue;
+        }
+
+        for _ in 1..r {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+impl ModpGroup {
+    // checks that p and q are prime, q divides p - 1 and both are large enough
+    pub fn validate(&self, minimum: &MinimumStrength) -> Result<(), ParameterError> {
+        let p = self.p();
+        let q = self.q();
+
+        if p.bits() < minimum.p_bits {
+            return Err(ParameterError::ModulusTooSmall {
+                bits: p.bits(),
+                minimum: minimum.p_bits,
+            });
+        }
+        if q.bits() < minimum.q_bits {
+            return Err(ParameterError::OrderTooSmall {
+                bits: q.bits(),
+                minimum: minimum.q_bits,
+            });
+        }
+        if !is_probable_prime(q) {
+            return Err(ParameterError::CompositeOrder);
+        }
+        if !is_probable_prime(p) {
+            return Err(ParameterError::CompositeModulus);
+        }
+        if (p - 1u32) % q != BigUint::ZERO {
+            return Err(ParameterError::OrderDoesNotDivide);
+        }
+
+        Ok(())
+    }
+
+    // q is prime, so every member of the subgroup other than 1 has order exactly q
+    pub fn validate_generator(
+        &self,
+        name: &'static str,
+        generator: &BigUint,
+    ) -> Result<(), ParameterError> {
+        if *generator == BigUint::from(1u32) || !self.is_member(generator) {
+            return Err(ParameterError::InvalidGenerator(name));
+        }
+
+        Ok(())
+    }
+}
+
+impl ModpGroup {
+    // nothing-up-my-sleeve generator: hash the seed and a counter onto [0, p),
+    // then raise to the cofactor (p - 1) / q to land in the order q subgroup.
+    // the first counter that does not give 1 is used, so anyone holding the seed
+    // can re-derive the generator and nobody knows its discrete log to any other base
+    pub fn derive_generator(&self, seed: &[u8]) -> (BigUint, u32) {
+        let one = BigUint::from(1u32);
+        let cofactor = (self.p() - 1u32) / self.q();
+
+        for counter in 0.. {
+            let h = self.hash_to_field(seed, counter);
+            let generator = h.modpow(&cofactor, self.p());
+
+            if generator > one {
+                return (generator, counter);
+            }
+        }
+
+        unreachable!("generator derivation exhausted every counter")
+    }
+
+    // true if generator is exactly what derive_generator produces for this seed
+    pub fn verify_generator(&self, seed: &[u8], generator: &BigUint) -> bool {
+        self.derive_generator(seed).0 == *generator
+    }
+
+    // expands SHA-512 to 64 bits more than p so the reduction mod p is close to uniform
+    fn hash_to_field(&self, seed: &[u8], counter: u32) -> BigUint {
+        let length = (self.p().bits() as usize).div_ceil(8) + 8;
+        let mut bytes = Vec::with_capacity(length + 64);
+
+        for block in 0u32.. {
+            if bytes.len() >= length {
+                break;
+            }
+
+            let mut hasher = Sha512::new();
+            hasher.update(GENERATOR_TAG);
+            hasher.update((seed.len() as u64).to_be_bytes());
+            hasher.update(seed);
+            hasher.update(counter.to_be_bytes());
+            hasher.update(block.to_be_bytes());
+            bytes.extend_from_slice(&hasher.finalize());
+        }
+
+        BigUint::from_bytes_be(&bytes[..length]) % self.p()
+    }
+}
@@ -1,24 +1,61 @@
-use std::io::stdin;
+use std::{
+    io::stdin,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-pub mod auth {
-    include!("./auth.rs");
-}
+use tonic::transport::Channel;
+use zeroize::{Zeroize, Zeroizing};
 
-use auth::auth_client::AuthClient;
-use num_bigint::BigUint;
+use cp_protocol::auth::{
+    self, AuthenticationChallengeRequest, KdfParametersRequest, LoginRequest, Protocol,
+    RegisterRequest, auth_client::AuthClient,
+};
 
-use crate::auth::{AuthenticationChallengeRequest, RegisterRequest};
+use cp_protocol::{
+    Backend, BoxedRng, Error, Group, Kdf, KdfError, MinimumStrength, ParameterSet, Proof,
+    Ristretto255, SecretScalar,
+    kdf::{derive_secret, generate_salt_with_rng},
+    rng_from_args,
+};
 
-use cp_protocol::Proof;
+// command line flags that shape a run
+struct Options {
+    kdf: Kdf,
+    non_interactive: bool,
+    deterministic: bool,
+    schnorr: bool,
+    rng: BoxedRng,
+}
 
 #[tokio::main]
 async fn main() {
+    let backend = Backend::from_args();
+    // --login answers with a single non-interactive proof instead of a challenge round trip
+    let non_interactive = std::env::args().any(|arg| arg == "--login");
+    // --deterministic-nonce derives k from the secret and the session instead of the RNG
+    let deterministic = std::env::args().any(|arg| arg == "--deterministic-nonce");
+    // --schnorr registers y1 alone and proves with half the exponentiations
+    let schnorr = std::env::args().any(|arg| arg == "--schnorr");
+    // --kdf=<argon2id|scrypt|pbkdf2> picks the password hashing used at registration
+    let kdf: Kdf = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--kdf=").map(str::to_string))
+        .map(|name| name.parse().expect("Could not parse the kdf"))
+        .unwrap_or_default();
+    // --seed=<u64> makes salts and nonces reproducible, for testing only
+    let rng = rng_from_args();
+    let mut options = Options {
+        kdf,
+        non_interactive,
+        deterministic,
+        schnorr,
+        rng,
+    };
     let mut buffer = String::new();
     let mut client = AuthClient::connect("http://127.0.0.1:50051")
         .await
         .expect("Could not connect to the verifier");
 
-    println!("Connected to the verifier");
+    println!("Connected to the verifier ({:?} backend)", backend);
     println!("Please provide your identifier:");
 
     stdin()
@@ -31,18 +68,102 @@ async fn main() {
     stdin()
         .read_line(&mut buffer)
         .expect("Could not get the password from stdin");
-    let password = BigUint::from_bytes_be(buffer.trim().as_bytes());
-    buffer.clear();
+    let password = Zeroizing::new(buffer.trim().to_string());
+    buffer.zeroize();
+
+    let result = match backend {
+        Backend::Modp => {
+            let params = ParameterSet::from_args();
+            println!("Using parameter set {} ({})", params.name, params.reference);
+            let proof = params
+                .proof(&MinimumStrength::default())
+                .expect("Invalid group parameters");
+            run(&mut client, proof, identifier, password, &mut options).await
+        }
+        Backend::Ristretto => {
+            let (alpha, beta) = Ristretto255::get_constants();
+            let proof = Proof::with_group(Ristretto255, alpha, beta);
+            run(&mut client, proof, identifier, password, &mut options).await
+        }
+    };
+
+    match result {
+        Ok(session_id) => println!("Authenticated with session_id: {}", session_id),
+        Err(error) => {
+            eprintln!("The verifier sent unusable kdf parameters: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
 
-    let (p, q, alpha, beta) = Proof::get_constants();
-    let proof = Proof::new(p, q, alpha, beta);
+// hashes the login password with the kdf and salt the verifier stored at registration. both
+// come from the verifier, so bad ones are an error rather than a panic
+async fn read_login_secret<G: Group>(
+    client: &mut AuthClient<Channel>,
+    group: &G,
+    identifier: &str,
+) -> Result<SecretScalar<G>, KdfError> {
+    let request = KdfParametersRequest {
+        identifier: identifier.to_string(),
+    };
 
-    let [y1, y2] = proof.create_pair(&password);
+    let kdf = client
+        .get_kdf_parameters(request)
+        .await
+        .expect("Failed to get the kdf parameters")
+        .into_inner();
+
+    let (kdf, salt) = Kdf::from_proto(kdf)?;
+    derive_secret(group, &kdf, read_login_password().as_bytes(), &salt)
+}
+
+fn unix_time() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the unix epoch")
+}
+
+fn read_login_password() -> Zeroizing<String> {
+    let mut buffer = Zeroizing::new(String::new());
+    println!("Please provide the password to log in:");
+    stdin()
+        .read_line(&mut buffer)
+        .expect("Could not get the password from stdin");
+    Zeroizing::new(buffer.trim().to_string())
+}
+
+async fn run<G: Group>(
+    client: &mut AuthClient<Channel>,
+    proof: Proof<G>,
+    identifier: String,
+    password: Zeroizing<String>,
+    options: &mut Options,
+) -> Result<String, KdfError> {
+    let group = proof.group();
+    let kdf = options.kdf;
+    let salt = generate_salt_with_rng(&mut options.rng);
+    let password = derive_secret(group, &kdf, password.as_bytes(), &salt)
+        .expect("Could not derive the secret from the password");
+
+    let (y1, y2, protocol) = if options.schnorr {
+        let y1 = proof
+            .schnorr()
+            .public_key(&password)
+            .expect("Could not create the public key");
+        (y1, None, Protocol::Schnorr)
+    } else {
+        let [y1, y2] = proof
+            .create_pair(&password)
+            .expect("Could not create the registration pair");
+        (y1, Some(y2), Protocol::ChaumPedersen)
+    };
 
     let request = RegisterRequest {
         identifier: identifier.clone(),
-        y1: y1.to_bytes_be(),
-        y2: y2.to_bytes_be(),
+        y1: group.encode_element(&y1),
+        y2: encode_optional(group, y2.as_ref()),
+        kdf: Some(kdf.to_proto(&salt)),
+        protocol: protocol.into(),
     };
 
     let _response = client
@@ -50,13 +171,81 @@ async fn main() {
         .await
         .expect("Failed to send registration request");
 
-    let k = proof.generate_random_number();
-    let [r1, r2] = proof.create_pair(&k);
+    if options.non_interactive {
+        return login(client, &proof, identifier, options).await;
+    }
+
+    let password = read_login_secret(client, group, &identifier).await?;
+
+    // the clock keeps the context unique per login, a repeated k would leak x. the clock
+    // alone can repeat after a snapshot restore or a step back, hence the extra entropy
+    let context = options.deterministic.then(|| {
+        let mut context = identifier.as_bytes().to_vec();
+        context.extend_from_slice(&unix_time().as_nanos().to_be_bytes());
+        (context, extra_entropy(&mut options.rng))
+    });
+
+    if options.schnorr {
+        let session = proof.schnorr().prover(&password);
+        let commitment = match &context {
+            Some((context, entropy)) => session.commit_deterministic(context, Some(&entropy[..])),
+            None => session.commit_with_rng(&mut options.rng),
+        }
+        .expect("Could not create the commitment");
 
+        let r1 = commitment.r1().clone();
+        return Ok(
+            answer_challenge(client, &proof, identifier, &r1, None, |c| {
+                commitment.respond(c)
+            })
+            .await,
+        );
+    }
+
+    let session = proof.prover(&password);
+    let commitment = match &context {
+        Some((context, entropy)) => session.commit_deterministic(context, Some(&entropy[..])),
+        None => session.commit_with_rng(&mut options.rng),
+    }
+    .expect("Could not create the commitment");
+
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    Ok(
+        answer_challenge(client, &proof, identifier, &r1, Some(&r2), |c| {
+            commitment.respond(c)
+        })
+        .await,
+    )
+}
+
+// fresh randomness mixed into deterministic nonces (RFC 6979 section 3.6)
+fn extra_entropy(rng: &mut BoxedRng) -> Zeroizing<[u8; 32]> {
+    let mut entropy = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(entropy.as_mut());
+    entropy
+}
+
+// absent elements are sent as empty bytes
+fn encode_optional<G: Group>(group: &G, element: Option<&G::Element>) -> Vec<u8> {
+    element
+        .map(|element| group.encode_element(element))
+        .unwrap_or_default()
+}
+
+// sends the commitment and answers the challenge the verifier returns with respond
+async fn answer_challenge<G: Group>(
+    client: &mut AuthClient<Channel>,
+    proof: &Proof<G>,
+    identifier: String,
+    r1: &G::Element,
+    r2: Option<&G::Element>,
+    respond: impl FnOnce(&G::Scalar) -> Result<G::Scalar, Error>,
+) -> String {
+    let group = proof.group();
     let request = AuthenticationChallengeRequest {
-        identifier: identifier.clone(),
-        r1: r1.to_bytes_be(),
-        r2: r2.to_bytes_be(),
+        identifier,
+        r1: group.encode_element(r1),
+        r2: encode_optional(group, r2),
     };
 
     let response = client
@@ -66,19 +255,15 @@ async fn main() {
         .into_inner();
 
     let auth_id = response.auth_id;
-    let c = BigUint::from_bytes_be(&response.c);
-
-    println!("Please provide the password to log in:");
-    stdin()
-        .read_line(&mut buffer)
-        .expect("Could not get the password from stdin");
-    let password = BigUint::from_bytes_be(buffer.trim().as_bytes());
+    let c = proof
+        .decode_scalar("c", &response.c)
+        .expect("Received an invalid challenge");
 
-    let s = proof.solve(&k, &c, &password);
+    let s = respond(&c).expect("Could not solve the challenge");
 
     let request = auth::AuthenticationAnswerRequest {
         auth_id,
-        s: s.to_bytes_be(),
+        s: group.encode_scalar(&s),
     };
 
     let response = client
@@ -87,5 +272,63 @@ async fn main() {
         .expect("Failed to verify authentication")
         .into_inner();
 
-    println!("Authenticated with session_id: {}", response.session_id);
+    response.session_id
+}
+
+async fn login<G: Group>(
+    client: &mut AuthClient<Channel>,
+    proof: &Proof<G>,
+    identifier: String,
+    options: &mut Options,
+) -> Result<String, KdfError> {
+    let group = proof.group();
+    let password = read_login_secret(client, group, &identifier).await?;
+
+    let timestamp = unix_time().as_secs();
+    let nonce = timestamp.to_be_bytes();
+
+    let (r1, r2, s) = if options.schnorr {
+        let schnorr = proof.schnorr();
+        let login_proof = if options.deterministic {
+            schnorr.prove_non_interactive_deterministic(
+                &password,
+                &identifier,
+                &nonce,
+                Some(&extra_entropy(&mut options.rng)[..]),
+            )
+        } else {
+            schnorr.prove_non_interactive_with_rng(&password, &identifier, &nonce, &mut options.rng)
+        }
+        .expect("Could not create the login proof");
+        (login_proof.r1, None, login_proof.s)
+    } else {
+        let login_proof = if options.deterministic {
+            proof.prove_non_interactive_deterministic(
+                &password,
+                &identifier,
+                &nonce,
+                Some(&extra_entropy(&mut options.rng)[..]),
+            )
+        } else {
+            proof.prove_non_interactive_with_rng(&password, &identifier, &nonce, &mut options.rng)
+        }
+        .expect("Could not create the login proof");
+        (login_proof.r1, Some(login_proof.r2), login_proof.s)
+    };
+
+    let request = LoginRequest {
+        identifier,
+        timestamp,
+        r1: group.encode_element(&r1),
+        r2: encode_optional(group, r2.as_ref()),
+        s: group.encode_scalar(&s),
+    };
+
+    let response = client
+        .login(request)
+        .await
+        .expect("Failed to log in")
+        .into_inner();
+
+    Ok(response.session_id)
 }
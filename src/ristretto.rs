@@ -0,0 +1,169 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoBasepointTable, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, VartimeMultiscalarMul};
+use rand_core::CryptoRngCore;
+use sha2::Sha512;
+use zeroize::Zeroize;
+
+use crate::group::Group;
+
+// prime order group built on Curve25519, elements and scalars are 32 bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Ristretto255;
+
+impl Ristretto255 {
+    pub fn get_constants() -> (RistrettoPoint, RistrettoPoint) {
+        let alpha = RISTRETTO_BASEPOINT_POINT;
+
+        // beta is hashed onto the curve so nobody knows log_alpha(beta)
+        let beta = RistrettoPoint::hash_from_bytes::<Sha512>(b"cp-protocol ristretto255 beta");
+
+        (alpha, beta)
+    }
+}
+
+// the curve group is additive, so exp is a scalar multiplication and mul a point addition
+impl Group for Ristretto255 {
+    type Element = RistrettoPoint;
+    type Scalar = Scalar;
+    type FixedBase = RistrettoBasepointTable;
+
+    fn parameters(&self) -> Vec<u8> {
+        b"ristretto255".to_vec()
+    }
+
+    fn identity(&self) -> RistrettoPoint {
+        RistrettoPoint::identity()
+    }
+
+    fn exp(&self, base: &RistrettoPoint, exp: &Scalar) -> RistrettoPoint {
+        base * exp
+    }
+
+    // dalek's scalar multiplication is constant time already
+    fn exp_secret(&self, base: &RistrettoPoint, exp: &Scalar) -> RistrettoPoint {
+        base * exp
+    }
+
+    fn mul(&self, a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
+        a + b
+    }
+
+    fn fixed_base(&self, base: &RistrettoPoint) -> RistrettoBasepointTable {
+        RistrettoBasepointTable::create(base)
+    }
+
+    fn exp_fixed_base(&self, table: &RistrettoBasepointTable, exp: &Scalar) -> RistrettoPoint {
+        table * exp
+    }
+
+    fn exp_fixed_base_secret(
+        &self,
+        table: &RistrettoBasepointTable,
+        exp: &Scalar,
+    ) -> RistrettoPoint {
+        table * exp
+    }
+
+    fn multi_exp(&self, terms: &[(&RistrettoPoint, &Scalar)]) -> RistrettoPoint {
+        RistrettoPoint::vartime_multiscalar_mul(
+            terms.iter().map(|(_, exp)| *exp),
+            terms.iter().map(|(base, _)| *base),
+        )
+    }
+
+    // a vartime multiscalar multiplication beats the constant time table lookups
+    fn multi_exp_fixed_base(
+        &self,
+        fixed: &[(&RistrettoBasepointTable, &Scalar)],
+        terms: &[(&RistrettoPoint, &Scalar)],
+    ) -> RistrettoPoint {
+        let bases: Vec<_> = fixed.iter().map(|(table, _)| table.basepoint()).collect();
+        RistrettoPoint::vartime_multiscalar_mul(
+            fixed
+                .iter()
+                .map(|(_, exp)| *exp)
+                .chain(terms.iter().map(|(_, exp)| *exp)),
+            bases.iter().chain(terms.iter().map(|(base, _)| *base)),
+        )
+    }
+
+    // every decoded ristretto point is in the prime order group
+    fn is_member(&self, _element: &RistrettoPoint) -> bool {
+        true
+    }
+
+    fn random_scalar(&self, rng: &mut dyn CryptoRngCore) -> Scalar {
+        Scalar::random(rng)
+    }
+
+    fn is_zero(&self, scalar: &Scalar) -> bool {
+        *scalar == Scalar::ZERO
+    }
+
+    // dalek only hands out scalars reduced mod l
+    fn is_valid_scalar(&self, _scalar: &Scalar) -> bool {
+        true
+    }
+
+    fn scalar_length(&self) -> usize {
+        32
+    }
+
+    fn scalar_add(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a + b
+    }
+
+    fn scalar_sub(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a - b
+    }
+
+    fn scalar_mul(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a * b
+    }
+
+    fn scalar_sub_mul(&self, k: &Scalar, c: &Scalar, x: &Scalar) -> Scalar {
+        k - c * x
+    }
+
+    fn scalar_invert(&self, a: &Scalar) -> Option<Scalar> {
+        if *a == Scalar::ZERO {
+            return None;
+        }
+        Some(a.invert())
+    }
+
+    fn scalar_from_u128(&self, value: u128) -> Scalar {
+        Scalar::from(value)
+    }
+
+    fn zeroize_scalar(scalar: &mut Scalar) {
+        scalar.zeroize();
+    }
+
+    fn scalar_from_bytes(&self, bytes: &[u8]) -> Scalar {
+        Scalar::hash_from_bytes::<Sha512>(bytes)
+    }
+
+    fn element_length(&self) -> usize {
+        32
+    }
+
+    fn encode_element(&self, element: &RistrettoPoint) -> Vec<u8> {
+        element.compress().to_bytes().to_vec()
+    }
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<RistrettoPoint> {
+        CompressedRistretto::from_slice(bytes).ok()?.decompress()
+    }
+
+    fn encode_scalar(&self, scalar: &Scalar) -> Vec<u8> {
+        scalar.to_bytes().to_vec()
+    }
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<Scalar> {
+        let bytes: [u8; 32] = bytes.try_into().ok()?;
+        Option::from(Scalar::from_canonical_bytes(bytes))
+    }
+}
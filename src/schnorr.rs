@@ -0,0 +1,229 @@
+use rand_core::{CryptoRngCore, OsRng};
+
+use crate::error::{Equation, Error};
+use crate::group::Group;
+use crate::secret::{Nonce, Secret, SecretScalar};
+use crate::{Generator, ModpGroup, Proof};
+
+// domain separation tags, distinct from the Chaum-Pedersen ones so a transcript of one
+// protocol never hashes like a transcript of the other
+const FIAT_SHAMIR_TAG: &[u8] = b"cp-protocol schnorr fiat-shamir v1";
+const NONCE_CONTEXT_TAG: &[u8] = b"cp-protocol schnorr nonce context v1";
+
+// Schnorr identification: proof of knowledge of x for y1 = alpha^x alone. it is the alpha
+// half of the Chaum-Pedersen proof, over the same group, generators and encodings, and
+// costs half the exponentiations. names follow the two-base protocol (y1, r1, Equation::R1)
+pub struct Schnorr<'a, G: Group = ModpGroup> {
+    proof: &'a Proof<G>,
+}
+
+// commitment and response of a Schnorr proof whose challenge is derived by hashing
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchnorrProof<G: Group> {
+    pub r1: G::Element,
+    pub s: G::Scalar,
+}
+
+// prover side of the interactive protocol, see ProverSession
+pub struct SchnorrSession<'a, G: Group> {
+    schnorr: Schnorr<'a, G>,
+    x: &'a SecretScalar<G>,
+}
+
+// one-time state between sending r1 and answering the challenge, see Commitment
+pub struct SchnorrCommitment<'a, G: Group> {
+    schnorr: Schnorr<'a, G>,
+    x: &'a SecretScalar<G>,
+    k: Nonce<G>,
+    r1: G::Element,
+}
+
+impl<G: Group> Proof<G> {
+    pub fn schnorr(&self) -> Schnorr<'_, G> {
+        Schnorr { proof: self }
+    }
+}
+
+impl<G: Group> Clone for Schnorr<'_, G> {
+    fn clone(&self) -> Self {
+        Schnorr { proof: self.proof }
+    }
+}
+
+impl<'a, G: Group> SchnorrSession<'a, G> {
+    pub fn commit(&self) -> Result<SchnorrCommitment<'a, G>, Error> {
+        self.commit_with_rng(&mut OsRng)
+    }
+
+    pub fn commit_with_rng(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<SchnorrCommitment<'a, G>, Error> {
+        self.commit_with(self.schnorr.proof.generate_nonce_with_rng(rng))
+    }
+
+    // k derived from x and context, consumes the session like
+    // ProverSession::commit_deterministic
+    pub fn commit_deterministic(
+        self,
+        context: &[u8],
+        extra_entropy: Option<&[u8]>,
+    ) -> Result<SchnorrCommitment<'a, G>, Error> {
+        let k = Nonce::derive(self.schnorr.proof.group(), self.x, context, extra_entropy);
+        self.commit_with(k)
+    }
+
+    fn commit_with(&self, k: Nonce<G>) -> Result<SchnorrCommitment<'a, G>, Error> {
+        let r1 = self.schnorr.public_key(&k)?;
+
+        Ok(SchnorrCommitment {
+            schnorr: self.schnorr.clone(),
+            x: self.x,
+            k,
+            r1,
+        })
+    }
+}
+
+impl<G: Group> SchnorrCommitment<'_, G> {
+    pub fn r1(&self) -> &G::Element {
+        &self.r1
+    }
+
+    // s = k - c * x mod q
+    pub fn respond(self, c: &G::Scalar) -> Result<G::Scalar, Error> {
+        self.schnorr.proof.solve(self.k, c, self.x)
+    }
+}
+
+impl<'a, G: Group> Schnorr<'a, G> {
+    // y1 = alpha^x
+    pub fn public_key<S: Secret<G>>(&self, secret: &S) -> Result<G::Element, Error> {
+        let exp = secret.expose_secret();
+        self.proof.check_scalar("exponent", exp)?;
+
+        Ok(self.proof.exp_generator_secret(Generator::Alpha, exp))
+    }
+
+    pub fn prover(&self, x: &'a SecretScalar<G>) -> SchnorrSession<'a, G> {
+        SchnorrSession {
+            schnorr: self.clone(),
+            x,
+        }
+    }
+
+    // r1 == alpha^s * y1^c
+    pub fn verify(
+        &self,
+        r1: &G::Element,
+        y1: &G::Element,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<(), Error> {
+        let proof = self.proof;
+        proof.check_element("y1", y1)?;
+        proof.check_scalar("c", c)?;
+        proof.check_scalar("s", s)?;
+
+        if proof.multi_exp_generators(&[(Generator::Alpha, s)], &[(y1, c)]) != *r1 {
+            proof.check_element("r1", r1)?;
+            return Err(Error::VerificationFailed(Equation::R1));
+        }
+
+        Ok(())
+    }
+
+    // c = H(params, alpha, beta, y1, r1, identifier, nonce) mod q
+    pub fn challenge(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        identifier: &str,
+        nonce: &[u8],
+    ) -> G::Scalar {
+        let group = self.proof.group();
+
+        let digest = self.proof.hash_transcript(
+            FIAT_SHAMIR_TAG,
+            [
+                group.encode_element(y1),
+                group.encode_element(r1),
+                identifier.as_bytes().to_vec(),
+                nonce.to_vec(),
+            ],
+        );
+
+        group.scalar_from_bytes(&digest)
+    }
+
+    pub fn prove_non_interactive(
+        &self,
+        x: &SecretScalar<G>,
+        identifier: &str,
+        nonce: &[u8],
+    ) -> Result<SchnorrProof<G>, Error> {
+        self.prove_non_interactive_with_rng(x, identifier, nonce, &mut OsRng)
+    }
+
+    pub fn prove_non_interactive_with_rng(
+        &self,
+        x: &SecretScalar<G>,
+        identifier: &str,
+        nonce: &[u8],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<SchnorrProof<G>, Error> {
+        let y1 = self.public_key(x)?;
+        let commitment = self.prover(x).commit_with_rng(rng)?;
+        self.prove_with_commitment(identifier, nonce, &y1, commitment)
+    }
+
+    // see Proof::prove_non_interactive_deterministic
+    pub fn prove_non_interactive_deterministic(
+        &self,
+        x: &SecretScalar<G>,
+        identifier: &str,
+        nonce: &[u8],
+        extra_entropy: Option<&[u8]>,
+    ) -> Result<SchnorrProof<G>, Error> {
+        let y1 = self.public_key(x)?;
+
+        let context = self.proof.hash_transcript(
+            NONCE_CONTEXT_TAG,
+            [
+                self.proof.group().encode_element(&y1),
+                identifier.as_bytes().to_vec(),
+                nonce.to_vec(),
+            ],
+        );
+
+        let commitment = self
+            .prover(x)
+            .commit_deterministic(&context, extra_entropy)?;
+        self.prove_with_commitment(identifier, nonce, &y1, commitment)
+    }
+
+    fn prove_with_commitment(
+        &self,
+        identifier: &str,
+        nonce: &[u8],
+        y1: &G::Element,
+        commitment: SchnorrCommitment<'_, G>,
+    ) -> Result<SchnorrProof<G>, Error> {
+        let r1 = commitment.r1().clone();
+        let c = self.challenge(y1, &r1, identifier, nonce);
+        let s = commitment.respond(&c)?;
+
+        Ok(SchnorrProof { r1, s })
+    }
+
+    pub fn verify_non_interactive(
+        &self,
+        y1: &G::Element,
+        identifier: &str,
+        nonce: &[u8],
+        proof: &SchnorrProof<G>,
+    ) -> Result<(), Error> {
+        let c = self.challenge(y1, &proof.r1, identifier, nonce);
+        self.verify(&proof.r1, y1, &c, &proof.s)
+    }
+}
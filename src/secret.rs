@@ -0,0 +1,114 @@
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha512};
+use std::fmt;
+use zeroize::Zeroizing;
+
+use crate::drbg::HmacDrbg;
+use crate::group::{Group, REDUCTION_MARGIN};
+
+// a scalar that must never leave the prover: wiped on drop and redacted in Debug
+pub trait Secret<G: Group>: private::Sealed {
+    fn expose_secret(&self) -> &G::Scalar;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+// the long term secret x, usually derived from the password
+pub struct SecretScalar<G: Group> {
+    scalar: G::Scalar,
+}
+
+// the per proof random k, knowing it and s reveals x
+pub struct Nonce<G: Group> {
+    scalar: G::Scalar,
+}
+
+impl<G: Group> SecretScalar<G> {
+    pub fn new(scalar: G::Scalar) -> Self {
+        SecretScalar { scalar }
+    }
+}
+
+impl<G: Group> Nonce<G> {
+    // a caller chosen k could be reused, so outside the crate only hazmat::nonce gives one
+    pub(crate) fn new(scalar: G::Scalar) -> Self {
+        Nonce { scalar }
+    }
+
+    // k = 0 makes r1 = 1 and s = -c * x, so it is drawn again
+    pub fn random(group: &G, rng: &mut dyn CryptoRngCore) -> Self {
+        loop {
+            let k = Nonce::new(group.random_scalar(rng));
+            if !group.is_zero(k.expose_secret()) {
+                return k;
+            }
+        }
+    }
+
+    // RFC 6979 style: k comes from an HMAC_DRBG seeded with x and a hash of the context,
+    // so a broken RNG cannot repeat or bias it. the same x and context always give the
+    // same k, the context must therefore be unique per challenge the nonce will answer.
+    // extra_entropy is mixed in as well when available (RFC 6979 section 3.6). x and the
+    // hash have a fixed length, so no other context and extra_entropy give the same seed
+    pub fn derive(
+        group: &G,
+        x: &SecretScalar<G>,
+        context: &[u8],
+        extra_entropy: Option<&[u8]>,
+    ) -> Self {
+        let x_bytes = Zeroizing::new(group.encode_scalar(x.expose_secret()));
+        let context = Sha512::digest(context);
+        let mut drbg = HmacDrbg::new(&x_bytes, &context, extra_entropy.unwrap_or_default());
+        let mut bytes = Zeroizing::new(vec![0u8; group.scalar_length() + REDUCTION_MARGIN]);
+
+        loop {
+            drbg.generate(&mut bytes);
+
+            let k = Nonce::new(group.scalar_from_bytes(&bytes));
+            if !group.is_zero(k.expose_secret()) {
+                return k;
+            }
+        }
+    }
+}
+
+impl<G: Group> private::Sealed for SecretScalar<G> {}
+impl<G: Group> private::Sealed for Nonce<G> {}
+
+impl<G: Group> Secret<G> for SecretScalar<G> {
+    fn expose_secret(&self) -> &G::Scalar {
+        &self.scalar
+    }
+}
+
+impl<G: Group> Secret<G> for Nonce<G> {
+    fn expose_secret(&self) -> &G::Scalar {
+        &self.scalar
+    }
+}
+
+impl<G: Group> Drop for SecretScalar<G> {
+    fn drop(&mut self) {
+        G::zeroize_scalar(&mut self.scalar);
+    }
+}
+
+impl<G: Group> Drop for Nonce<G> {
+    fn drop(&mut self) {
+        G::zeroize_scalar(&mut self.scalar);
+    }
+}
+
+impl<G: Group> fmt::Debug for SecretScalar<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretScalar(<redacted>)")
+    }
+}
+
+impl<G: Group> fmt::Debug for Nonce<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Nonce(<redacted>)")
+    }
+}
@@ -0,0 +1,920 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use crate::kdf::Kdf;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+// answered commitments remembered per user to catch a prover reusing its nonce. only
+// verified answers count, so nobody without x can push the remembered ones out
+pub const MAX_COMMITMENTS: usize = 32;
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    // the backing storage holds something this version cannot read
+    Corrupt(String),
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(error) => write!(f, "credential store i/o failed: {}", error),
+            StoreError::Corrupt(reason) => write!(f, "credential store is corrupt: {}", reason),
+            #[cfg(feature = "sqlite")]
+            StoreError::Sqlite(error) => write!(f, "credential store query failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Io(error) => Some(error),
+            #[cfg(feature = "sqlite")]
+            StoreError::Sqlite(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(error: io::Error) -> Self {
+        StoreError::Io(error)
+    }
+}
+
+// everything the verifier keeps about a user between logins. elements and scalars are in
+// the canonical encoding of the verifier's group, so a store never depends on the group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registration {
+    pub identifier: String,
+    pub y1: Vec<u8>,
+    // None for single-base (Schnorr) credentials
+    pub y2: Option<Vec<u8>>,
+    pub kdf: Kdf,
+    pub salt: Vec<u8>,
+    // recently answered commitments, oldest first
+    pub commitments: VecDeque<SeenCommitment>,
+    // two valid answers to one commitment were seen, so x has leaked
+    pub compromised: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeenCommitment {
+    pub r1: Vec<u8>,
+    // empty for Schnorr commitments
+    pub r2: Vec<u8>,
+    // the verified answer to it
+    pub c: Vec<u8>,
+    pub s: Vec<u8>,
+}
+
+impl Registration {
+    pub fn new(
+        identifier: String,
+        y1: Vec<u8>,
+        y2: Option<Vec<u8>>,
+        kdf: Kdf,
+        salt: Vec<u8>,
+    ) -> Self {
+        Registration {
+            identifier,
+            y1,
+            y2,
+            kdf,
+            salt,
+            commitments: VecDeque::new(),
+            compromised: false,
+        }
+    }
+
+    // an empty r2 (Schnorr) must not match every other empty r2
+    pub fn seen_commitment(&self, r1: &[u8], r2: &[u8]) -> bool {
+        self.commitments
+            .iter()
+            .any(|seen| seen.r1 == r1 || (!r2.is_empty() && seen.r2 == r2))
+    }
+
+    // records a verified answer, flags the account if the commitment already answered a
+    // different challenge. c is canonically encoded, so equal bytes mean equal challenges
+    pub fn record_transcript(&mut self, r1: Vec<u8>, r2: Vec<u8>, c: Vec<u8>, s: Vec<u8>) {
+        match self.commitments.iter().find(|seen| seen.r1 == r1) {
+            Some(seen) => self.compromised |= seen.c != c,
+            None => {
+                if self.commitments.len() == MAX_COMMITMENTS {
+                    self.commitments.pop_front();
+                }
+                self.commitments.push_back(SeenCommitment { r1, r2, c, s });
+            }
+        }
+    }
+}
+
+// where the verifier keeps registrations. every method is atomic on its own, a read
+// followed by a write goes through compare_and_set (or update) so that two requests for
+// the same user never lose each other's changes
+pub trait CredentialStore: Send + Sync {
+    fn get(&self, identifier: &str) -> Result<Option<Registration>, StoreError>;
+
+    // inserts the registration or replaces the one under the same identifier
+    fn put(&self, registration: Registration) -> Result<(), StoreError>;
+
+    // false if there was nothing to delete
+    fn delete(&self, identifier: &str) -> Result<bool, StoreError>;
+
+    // every registration, ordered by identifier
+    fn list(&self) -> Result<Vec<Registration>, StoreError>;
+
+    // stores new only if the registration under its identifier still equals expected,
+    // None meaning there is none. false if another write got in between
+    fn compare_and_set(
+        &self,
+        expected: Option<&Registration>,
+        new: Registration,
+    ) -> Result<bool, StoreError>;
+
+    // read, modify and compare_and_set until no other write gets in between. f may run
+    // more than once. None if identifier is not registered
+    fn update<T>(
+        &self,
+        identifier: &str,
+        mut f: impl FnMut(&mut Registration) -> T,
+    ) -> Result<Option<T>, StoreError>
+    where
+        Self: Sized,
+    {
+        loop {
+            let Some(current) = self.get(identifier)? else {
+                return Ok(None);
+            };
+
+            let mut updated = current.clone();
+            let value = f(&mut updated);
+            if updated == current || self.compare_and_set(Some(&current), updated)? {
+                return Ok(Some(value));
+            }
+        }
+    }
+}
+
+// proof that a user logged in, handed to downstream services as a bearer token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub session_id: String,
+    // the user the session was issued to
+    pub identifier: String,
+    // unix seconds, the session is valid while now < expires_at
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+impl Session {
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        now < self.expires_at
+    }
+}
+
+// session ids are bearer tokens, so stores only keep this hash of them and look sessions up
+// by it. whoever can read a store cannot use the sessions in it
+pub fn session_hash(session_id: &str) -> String {
+    hex::encode(Sha256::digest(session_id.as_bytes()))
+}
+
+// where the verifier keeps the sessions it issued, every method is atomic on its own.
+// sessions come back with the id they were looked up by, see session_hash
+pub trait SessionStore: Send + Sync {
+    fn put_session(&self, session: Session) -> Result<(), StoreError>;
+
+    // None if the session is unknown or expired at now
+    fn get_session(&self, session_id: &str, now: u64) -> Result<Option<Session>, StoreError>;
+
+    // moves expires_at of a session still valid at now, None if it is unknown or expired
+    fn refresh_session(
+        &self,
+        session_id: &str,
+        now: u64,
+        expires_at: u64,
+    ) -> Result<Option<Session>, StoreError>;
+
+    // false if there was nothing to delete
+    fn delete_session(&self, session_id: &str) -> Result<bool, StoreError>;
+
+    // ends every session of a user, returns how many there were
+    fn delete_sessions_of(&self, identifier: &str) -> Result<usize, StoreError>;
+
+    // forgets sessions expired at now, returns how many there were
+    fn delete_expired_sessions(&self, now: u64) -> Result<usize, StoreError>;
+}
+
+// compare_and_set on a map, shared by the stores that keep everything in memory
+fn compare_and_set_in(
+    registrations: &mut BTreeMap<String, Registration>,
+    expected: Option<&Registration>,
+    new: Registration,
+) -> bool {
+    if registrations.get(&new.identifier) != expected {
+        return false;
+    }
+    registrations.insert(new.identifier.clone(), new);
+    true
+}
+
+// the stores that keep sessions in a map keep them under session_hash, with the hash in
+// place of the id
+fn hashed(session: Session) -> Session {
+    Session {
+        session_id: session_hash(&session.session_id),
+        ..session
+    }
+}
+
+fn get_in(sessions: &HashMap<String, Session>, session_id: &str, now: u64) -> Option<Session> {
+    let session = sessions.get(&session_hash(session_id))?;
+    session.is_valid_at(now).then(|| Session {
+        session_id: session_id.to_string(),
+        ..session.clone()
+    })
+}
+
+fn refresh_in(
+    sessions: &mut HashMap<String, Session>,
+    session_id: &str,
+    now: u64,
+    expires_at: u64,
+) -> Option<Session> {
+    let session = sessions.get_mut(&session_hash(session_id))?;
+    if !session.is_valid_at(now) {
+        return None;
+    }
+    session.expires_at = expires_at;
+    Some(Session {
+        session_id: session_id.to_string(),
+        ..session.clone()
+    })
+}
+
+// lost on restart, as the verifier used to behave
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    registrations: Mutex<BTreeMap<String, Registration>>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl CredentialStore for MemoryStore {
+    fn get(&self, identifier: &str) -> Result<Option<Registration>, StoreError> {
+        Ok(self.registrations.lock().unwrap().get(identifier).cloned())
+    }
+
+    fn put(&self, registration: Registration) -> Result<(), StoreError> {
+        let registrations = &mut self.registrations.lock().unwrap();
+        registrations.insert(registration.identifier.clone(), registration);
+        Ok(())
+    }
+
+    fn delete(&self, identifier: &str) -> Result<bool, StoreError> {
+        Ok(self
+            .registrations
+            .lock()
+            .unwrap()
+            .remove(identifier)
+            .is_some())
+    }
+
+    fn list(&self) -> Result<Vec<Registration>, StoreError> {
+        Ok(self
+            .registrations
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    fn compare_and_set(
+        &self,
+        expected: Option<&Registration>,
+        new: Registration,
+    ) -> Result<bool, StoreError> {
+        let registrations = &mut self.registrations.lock().unwrap();
+        Ok(compare_and_set_in(registrations, expected, new))
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn put_session(&self, session: Session) -> Result<(), StoreError> {
+        let session = hashed(session);
+        let sessions = &mut self.sessions.lock().unwrap();
+        sessions.insert(session.session_id.clone(), session);
+        Ok(())
+    }
+
+    fn get_session(&self, session_id: &str, now: u64) -> Result<Option<Session>, StoreError> {
+        let sessions = &self.sessions.lock().unwrap();
+        Ok(get_in(sessions, session_id, now))
+    }
+
+    fn refresh_session(
+        &self,
+        session_id: &str,
+        now: u64,
+        expires_at: u64,
+    ) -> Result<Option<Session>, StoreError> {
+        let sessions = &mut self.sessions.lock().unwrap();
+        Ok(refresh_in(sessions, session_id, now, expires_at))
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<bool, StoreError> {
+        let sessions = &mut self.sessions.lock().unwrap();
+        Ok(sessions.remove(&session_hash(session_id)).is_some())
+    }
+
+    fn delete_sessions_of(&self, identifier: &str) -> Result<usize, StoreError> {
+        let sessions = &mut self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, session| session.identifier != identifier);
+        Ok(before - sessions.len())
+    }
+
+    fn delete_expired_sessions(&self, now: u64) -> Result<usize, StoreError> {
+        let sessions = &mut self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, session| session.is_valid_at(now));
+        Ok(before - sessions.len())
+    }
+}
+
+// first line of every credential file, bumped on any incompatible change
+const FILE_HEADER: &str = "cp-protocol credentials v1";
+
+// first line of the commitments log next to it
+const COMMITMENTS_HEADER: &str = "cp-protocol commitments v1";
+
+// first line of the sessions file next to it
+const SESSIONS_HEADER: &str = "cp-protocol sessions v1";
+
+// lines the commitments log may gain beyond twice what compacting it last left behind
+const COMPACT_SLACK: usize = 1024;
+
+// keeps every registration in memory, reads never touch the disk. registrations are
+// rewritten as a whole through a temporary file and a rename, so a crash leaves either the
+// old or the new version. that is about 1.1 KB per user on 2048-bit modp and only happens
+// on register, delete and when an account gets flagged.
+//
+// the commitments change on every login, so they are appended to a log at the same
+// path with .commitments appended instead, and the log is compacted once it has doubled.
+// a crash while a registration is replaced can at worst forget the commitments of the
+// one it replaces.
+//
+// one registration per line, space separated:
+//   identifier y1 y2 kdf salt compromised
+// with byte strings in hex, "-" for a missing y2, the kdf as name:parameters
+// (see kdf_to_spec) and compromised as 0 or 1.
+//
+// the log has one change per line, "identifier r1:r2:c:s" for an answered commitment
+// (see apply_commitment) and "identifier -" where the registration was replaced or
+// deleted.
+//
+// sessions go to the same path with .sessions appended, rewritten like the registrations,
+// one per line:
+//   session_hash identifier created_at expires_at
+// with the identifier in hex, see session_hash
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    registrations: Mutex<BTreeMap<String, Registration>>,
+    // only locked while holding registrations
+    log: Mutex<CommitmentLog>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+#[derive(Debug)]
+struct CommitmentLog {
+    file: fs::File,
+    lines: usize,
+    // compacted before the next write once lines reaches this
+    compact_at: usize,
+}
+
+impl CommitmentLog {
+    fn append(&mut self, lines: &str) -> Result<(), StoreError> {
+        self.file.write_all(lines.as_bytes())?;
+        self.file.sync_data()?;
+        self.lines += lines.lines().count();
+        Ok(())
+    }
+}
+
+impl FileStore {
+    // loads the file at path, or starts empty if it does not exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let path = path.as_ref().to_path_buf();
+        let mut registrations = match read_if_exists(&path)? {
+            Some(contents) => parse_file(&contents)?,
+            None => BTreeMap::new(),
+        };
+        if let Some(contents) = read_if_exists(&commitments_path(&path))? {
+            replay_commitments(&contents, &mut registrations)?;
+        }
+        let log = compact_commitments(&path, &registrations)?;
+        let sessions = match read_if_exists(&sessions_path(&path))? {
+            Some(contents) => parse_sessions(&contents)?,
+            None => HashMap::new(),
+        };
+
+        Ok(FileStore {
+            path,
+            registrations: Mutex::new(registrations),
+            log: Mutex::new(log),
+            sessions: Mutex::new(sessions),
+        })
+    }
+
+    // puts new under identifier, or deletes it for None. called with the registrations
+    // lock held, so writes reach the disk in the order they happened
+    fn write(
+        &self,
+        registrations: &mut BTreeMap<String, Registration>,
+        identifier: &str,
+        new: Option<Registration>,
+    ) -> Result<(), StoreError> {
+        let log = &mut self.log.lock().unwrap();
+        if log.lines >= log.compact_at {
+            **log = compact_commitments(&self.path, registrations)?;
+        }
+
+        let old = registrations.get(identifier);
+        let changes = match (old, &new) {
+            (Some(old), Some(new)) => commitment_changes(old, new),
+            _ => None,
+        };
+
+        match changes {
+            Some(changes) => {
+                let lines: String = changes
+                    .iter()
+                    .map(|seen| format_log_line(identifier, Some(seen)))
+                    .collect();
+                log.append(&lines)?;
+            }
+            None => {
+                let mut lines = format_log_line(identifier, None);
+                for seen in new.iter().flat_map(|new| &new.commitments) {
+                    lines.push_str(&format_log_line(identifier, Some(seen)));
+                }
+                log.append(&lines)?;
+
+                let mut updated = registrations.clone();
+                match &new {
+                    Some(new) => updated.insert(identifier.to_string(), new.clone()),
+                    None => updated.remove(identifier),
+                };
+                self.save(&updated)?;
+            }
+        }
+
+        match new {
+            Some(new) => registrations.insert(identifier.to_string(), new),
+            None => registrations.remove(identifier),
+        };
+        Ok(())
+    }
+
+    // the registrations without their commitments, see write
+    fn save(&self, registrations: &BTreeMap<String, Registration>) -> Result<(), StoreError> {
+        let mut contents = format!("{}\n", FILE_HEADER);
+        for registration in registrations.values() {
+            contents.push_str(&format_line(registration));
+            contents.push('\n');
+        }
+
+        write_atomically(&self.path, &contents)
+    }
+
+    // called with the sessions lock held, like save
+    fn save_sessions(&self, sessions: &HashMap<String, Session>) -> Result<(), StoreError> {
+        let mut sorted: Vec<&Session> = sessions.values().collect();
+        sorted.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+
+        let mut contents = format!("{}\n", SESSIONS_HEADER);
+        for session in sorted {
+            contents.push_str(&format!(
+                "{} {} {} {}\n",
+                session.session_id,
+                hex::encode(&session.identifier),
+                session.created_at,
+                session.expires_at
+            ));
+        }
+
+        write_atomically(&sessions_path(&self.path), &contents)
+    }
+
+    // applies f to a copy of the sessions and keeps it once it is on disk
+    fn modify_sessions<T>(
+        &self,
+        f: impl FnOnce(&mut HashMap<String, Session>) -> T,
+    ) -> Result<T, StoreError> {
+        let sessions = &mut self.sessions.lock().unwrap();
+        let mut updated = sessions.clone();
+        let value = f(&mut updated);
+        if updated != **sessions {
+            self.save_sessions(&updated)?;
+            **sessions = updated;
+        }
+        Ok(value)
+    }
+}
+
+impl CredentialStore for FileStore {
+    fn get(&self, identifier: &str) -> Result<Option<Registration>, StoreError> {
+        Ok(self.registrations.lock().unwrap().get(identifier).cloned())
+    }
+
+    fn put(&self, registration: Registration) -> Result<(), StoreError> {
+        let registrations = &mut self.registrations.lock().unwrap();
+        let identifier = registration.identifier.clone();
+        self.write(registrations, &identifier, Some(registration))
+    }
+
+    fn delete(&self, identifier: &str) -> Result<bool, StoreError> {
+        let registrations = &mut self.registrations.lock().unwrap();
+        if !registrations.contains_key(identifier) {
+            return Ok(false);
+        }
+        self.write(registrations, identifier, None)?;
+        Ok(true)
+    }
+
+    fn list(&self) -> Result<Vec<Registration>, StoreError> {
+        Ok(self
+            .registrations
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    fn compare_and_set(
+        &self,
+        expected: Option<&Registration>,
+        new: Registration,
+    ) -> Result<bool, StoreError> {
+        let registrations = &mut self.registrations.lock().unwrap();
+        if registrations.get(&new.identifier) != expected {
+            return Ok(false);
+        }
+        let identifier = new.identifier.clone();
+        self.write(registrations, &identifier, Some(new))?;
+        Ok(true)
+    }
+}
+
+impl SessionStore for FileStore {
+    fn put_session(&self, session: Session) -> Result<(), StoreError> {
+        let session = hashed(session);
+        self.modify_sessions(|sessions| {
+            sessions.insert(session.session_id.clone(), session);
+        })
+    }
+
+    fn get_session(&self, session_id: &str, now: u64) -> Result<Option<Session>, StoreError> {
+        let sessions = &self.sessions.lock().unwrap();
+        Ok(get_in(sessions, session_id, now))
+    }
+
+    fn refresh_session(
+        &self,
+        session_id: &str,
+        now: u64,
+        expires_at: u64,
+    ) -> Result<Option<Session>, StoreError> {
+        self.modify_sessions(|sessions| refresh_in(sessions, session_id, now, expires_at))
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<bool, StoreError> {
+        self.modify_sessions(|sessions| sessions.remove(&session_hash(session_id)).is_some())
+    }
+
+    fn delete_sessions_of(&self, identifier: &str) -> Result<usize, StoreError> {
+        self.modify_sessions(|sessions| {
+            let before = sessions.len();
+            sessions.retain(|_, session| session.identifier != identifier);
+            before - sessions.len()
+        })
+    }
+
+    fn delete_expired_sessions(&self, now: u64) -> Result<usize, StoreError> {
+        self.modify_sessions(|sessions| {
+            let before = sessions.len();
+            sessions.retain(|_, session| session.is_valid_at(now));
+            before - sessions.len()
+        })
+    }
+}
+
+fn sessions_path(path: &Path) -> PathBuf {
+    let mut sessions = path.to_path_buf().into_os_string();
+    sessions.push(".sessions");
+    sessions.into()
+}
+
+fn commitments_path(path: &Path) -> PathBuf {
+    let mut commitments = path.to_path_buf().into_os_string();
+    commitments.push(".commitments");
+    commitments.into()
+}
+
+// the change to commitments a log line stands for. mirrors record_transcript, except for
+// the flag that goes to the registration itself
+fn apply_commitment(registration: &mut Registration, seen: SeenCommitment) {
+    if registration
+        .commitments
+        .iter()
+        .any(|existing| existing.r1 == seen.r1)
+    {
+        return;
+    }
+    if registration.commitments.len() == MAX_COMMITMENTS {
+        registration.commitments.pop_front();
+    }
+    registration.commitments.push_back(seen);
+}
+
+// the log lines that turn old into new, None if anything besides the commitments changed
+// or replaying the lines would not give exactly new
+fn commitment_changes(old: &Registration, new: &Registration) -> Option<Vec<SeenCommitment>> {
+    let changes: Vec<SeenCommitment> = new
+        .commitments
+        .iter()
+        .filter(|seen| !old.commitments.contains(seen))
+        .cloned()
+        .collect();
+
+    let mut replayed = old.clone();
+    for seen in changes.iter().cloned() {
+        apply_commitment(&mut replayed, seen);
+    }
+    (replayed == *new).then_some(changes)
+}
+
+// rewrites the log with just the commitments still remembered and opens it for appending
+fn compact_commitments(
+    path: &Path,
+    registrations: &BTreeMap<String, Registration>,
+) -> Result<CommitmentLog, StoreError> {
+    let log_path = commitments_path(path);
+    let mut contents = format!("{}\n", COMMITMENTS_HEADER);
+    let mut lines = 0;
+    for registration in registrations.values() {
+        for seen in &registration.commitments {
+            contents.push_str(&format_log_line(&registration.identifier, Some(seen)));
+            lines += 1;
+        }
+    }
+    write_atomically(&log_path, &contents)?;
+
+    Ok(CommitmentLog {
+        file: fs::OpenOptions::new().append(true).open(&log_path)?,
+        lines,
+        compact_at: 2 * lines + COMPACT_SLACK,
+    })
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<String>, StoreError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+// through a temporary file and a rename, so readers see the old or the new contents
+fn write_atomically(path: &Path, contents: &str) -> Result<(), StoreError> {
+    let mut temporary = path.to_path_buf().into_os_string();
+    temporary.push(".tmp");
+    let mut file = fs::File::create(&temporary)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+// argon2id:memory_kib:iterations:parallelism, scrypt:log_n:r:p or pbkdf2:iterations
+pub fn kdf_to_spec(kdf: &Kdf) -> String {
+    match *kdf {
+        Kdf::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+        } => format!("argon2id:{}:{}:{}", memory_kib, iterations, parallelism),
+        Kdf::Scrypt { log_n, r, p } => format!("scrypt:{}:{}:{}", log_n, r, p),
+        Kdf::Pbkdf2Sha512 { iterations } => format!("pbkdf2:{}", iterations),
+    }
+}
+
+pub fn kdf_from_spec(spec: &str) -> Option<Kdf> {
+    let fields: Vec<&str> = spec.split(':').collect();
+    match fields[..] {
+        ["argon2id", memory_kib, iterations, parallelism] => Some(Kdf::Argon2id {
+            memory_kib: memory_kib.parse().ok()?,
+            iterations: iterations.parse().ok()?,
+            parallelism: parallelism.parse().ok()?,
+        }),
+        ["scrypt", log_n, r, p] => Some(Kdf::Scrypt {
+            log_n: log_n.parse().ok()?,
+            r: r.parse().ok()?,
+            p: p.parse().ok()?,
+        }),
+        ["pbkdf2", iterations] => Some(Kdf::Pbkdf2Sha512 {
+            iterations: iterations.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+fn format_line(registration: &Registration) -> String {
+    let fields = [
+        hex::encode(&registration.identifier),
+        hex::encode(&registration.y1),
+        registration
+            .y2
+            .as_ref()
+            .map(hex::encode)
+            .unwrap_or_else(|| "-".to_string()),
+        kdf_to_spec(&registration.kdf),
+        hex::encode(&registration.salt),
+        (registration.compromised as u8).to_string(),
+    ];
+
+    fields.join(" ")
+}
+
+// None for the line that resets the user's commitments
+fn format_log_line(identifier: &str, seen: Option<&SeenCommitment>) -> String {
+    let Some(seen) = seen else {
+        return format!("{} -\n", hex::encode(identifier));
+    };
+
+    format!(
+        "{} {}:{}:{}:{}\n",
+        hex::encode(identifier),
+        hex::encode(&seen.r1),
+        hex::encode(&seen.r2),
+        hex::encode(&seen.c),
+        hex::encode(&seen.s)
+    )
+}
+
+fn parse_file(contents: &str) -> Result<BTreeMap<String, Registration>, StoreError> {
+    let mut lines = contents.lines();
+    if lines.next() != Some(FILE_HEADER) {
+        return Err(StoreError::Corrupt(format!(
+            "missing header {:?}",
+            FILE_HEADER
+        )));
+    }
+
+    let mut registrations = BTreeMap::new();
+    for (index, line) in lines.enumerate() {
+        // the header is line 1
+        let corrupt = || StoreError::Corrupt(format!("cannot parse line {}", index + 2));
+        let registration = parse_line(line).ok_or_else(corrupt)?;
+        registrations.insert(registration.identifier.clone(), registration);
+    }
+
+    Ok(registrations)
+}
+
+fn parse_line(line: &str) -> Option<Registration> {
+    let mut fields = line.split(' ');
+    let mut next = || fields.next();
+
+    let identifier = String::from_utf8(hex::decode(next()?).ok()?).ok()?;
+    let y1 = hex::decode(next()?).ok()?;
+    let y2 = match next()? {
+        "-" => None,
+        y2 => Some(hex::decode(y2).ok()?),
+    };
+    let kdf = kdf_from_spec(next()?)?;
+    let salt = hex::decode(next()?).ok()?;
+    let compromised = match next()? {
+        "0" => false,
+        "1" => true,
+        _ => return None,
+    };
+
+    if fields.next().is_some() {
+        return None;
+    }
+
+    let mut registration = Registration::new(identifier, y1, y2, kdf, salt);
+    registration.compromised = compromised;
+    Some(registration)
+}
+
+// r1:r2:c:s
+fn parse_commitment(commitment: &str) -> Option<SeenCommitment> {
+    let parts = commitment
+        .split(':')
+        .map(hex::decode)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+
+    let [r1, r2, c, s] = <[Vec<u8>; 4]>::try_from(parts).ok()?;
+    Some(SeenCommitment { r1, r2, c, s })
+}
+
+fn replay_commitments(
+    contents: &str,
+    registrations: &mut BTreeMap<String, Registration>,
+) -> Result<(), StoreError> {
+    // a crash in the middle of an append leaves part of a line, which never reached
+    // the registration in memory either
+    let complete = &contents[..contents.rfind('\n').map_or(0, |end| end + 1)];
+
+    let mut lines = complete.lines();
+    if lines.next() != Some(COMMITMENTS_HEADER) {
+        return Err(StoreError::Corrupt(format!(
+            "missing header {:?}",
+            COMMITMENTS_HEADER
+        )));
+    }
+
+    for (index, line) in lines.enumerate() {
+        let corrupt = || StoreError::Corrupt(format!("cannot parse commitment line {}", index + 2));
+        let (identifier, change) = line.split_once(' ').ok_or_else(corrupt)?;
+        let identifier = hex::decode(identifier)
+            .ok()
+            .and_then(|identifier| String::from_utf8(identifier).ok())
+            .ok_or_else(corrupt)?;
+        let change = match change {
+            "-" => None,
+            commitment => Some(parse_commitment(commitment).ok_or_else(corrupt)?),
+        };
+
+        // lines about a registration that was deleted since have nothing to apply to
+        let Some(registration) = registrations.get_mut(&identifier) else {
+            continue;
+        };
+        match change {
+            Some(seen) => apply_commitment(registration, seen),
+            None => registration.commitments.clear(),
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_sessions(contents: &str) -> Result<HashMap<String, Session>, StoreError> {
+    let mut lines = contents.lines();
+    if lines.next() != Some(SESSIONS_HEADER) {
+        return Err(StoreError::Corrupt(format!(
+            "missing header {:?}",
+            SESSIONS_HEADER
+        )));
+    }
+
+    let mut sessions = HashMap::new();
+    for (index, line) in lines.enumerate() {
+        let corrupt = || StoreError::Corrupt(format!("cannot parse session line {}", index + 2));
+        let session = parse_session(line).ok_or_else(corrupt)?;
+        sessions.insert(session.session_id.clone(), session);
+    }
+
+    Ok(sessions)
+}
+
+// the session under its hash, which must be a SHA-256 in hex
+fn parse_session(line: &str) -> Option<Session> {
+    let fields: Vec<&str> = line.split(' ').collect();
+    let [session_hash, identifier, created_at, expires_at] = fields[..] else {
+        return None;
+    };
+    if hex::decode(session_hash).ok()?.len() != 32 {
+        return None;
+    }
+
+    Some(Session {
+        session_id: session_hash.to_string(),
+        identifier: String::from_utf8(hex::decode(identifier).ok()?).ok()?,
+        created_at: created_at.parse().ok()?,
+        expires_at: expires_at.parse().ok()?,
+    })
+}
@@ -0,0 +1,414 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior, params};
+
+use super::{
+    CredentialStore, Registration, SeenCommitment, Session, SessionStore, StoreError,
+    kdf_from_spec, kdf_to_spec, session_hash,
+};
+
+// applied in order when a database is opened, PRAGMA user_version counts the ones already
+// applied. append only, never edit a migration that has shipped
+const MIGRATIONS: &[&str] = &[
+    "
+    -- group_id tells apart registrations made under different groups or parameter sets,
+    -- a key only means something under the group it was made in
+    CREATE TABLE registrations (
+        group_id TEXT NOT NULL,
+        identifier TEXT NOT NULL,
+        y1 BLOB NOT NULL,
+        -- NULL for single-base (Schnorr) credentials
+        y2 BLOB,
+        -- name:parameters, see kdf_to_spec
+        kdf TEXT NOT NULL,
+        salt BLOB NOT NULL,
+        compromised INTEGER NOT NULL DEFAULT 0,
+        -- unix seconds
+        registered_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (group_id, identifier)
+    );
+
+    -- recently answered commitments of each user, position 0 is the oldest
+    CREATE TABLE commitments (
+        group_id TEXT NOT NULL,
+        identifier TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        r1 BLOB NOT NULL,
+        r2 BLOB NOT NULL,
+        -- the verified answer
+        c BLOB NOT NULL,
+        s BLOB NOT NULL,
+        PRIMARY KEY (group_id, identifier, position),
+        FOREIGN KEY (group_id, identifier) REFERENCES registrations ON DELETE CASCADE
+    );
+",
+    "
+    -- sessions issued after a successful proof, valid while unix time < expires_at. looked
+    -- up by the SHA-256 of their id, see session_hash
+    CREATE TABLE sessions (
+        session_hash TEXT PRIMARY KEY,
+        group_id TEXT NOT NULL,
+        identifier TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX sessions_by_user ON sessions (group_id, identifier);
+",
+];
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(error: rusqlite::Error) -> Self {
+        StoreError::Sqlite(error)
+    }
+}
+
+// registrations of one group in a SQLite database, which survives restarts and can be
+// looked at with the sqlite3 shell. writes run in immediate transactions, so several
+// processes may share a database
+#[derive(Debug)]
+pub struct SqliteStore {
+    connection: Mutex<Connection>,
+    group_id: String,
+}
+
+impl SqliteStore {
+    // creates the database if needed and brings its schema up to date
+    pub fn open(path: impl AsRef<Path>, group_id: &str) -> Result<Self, StoreError> {
+        let mut connection = Connection::open(path)?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut connection)?;
+
+        Ok(SqliteStore {
+            connection: Mutex::new(connection),
+            group_id: group_id.to_string(),
+        })
+    }
+
+    pub fn schema_version() -> usize {
+        MIGRATIONS.len()
+    }
+
+    fn read(
+        &self,
+        connection: &Connection,
+        identifier: &str,
+    ) -> Result<Option<Registration>, StoreError> {
+        let row = connection
+            .query_row(
+                "SELECT y1, y2, kdf, salt, compromised FROM registrations
+                 WHERE group_id = ?1 AND identifier = ?2",
+                params![self.group_id, identifier],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let Some((y1, y2, kdf, salt, compromised)) = row else {
+            return Ok(None);
+        };
+        let kdf = kdf_from_spec(&kdf)
+            .ok_or_else(|| StoreError::Corrupt(format!("unknown kdf {:?}", kdf)))?;
+
+        let mut registration = Registration::new(identifier.to_string(), y1, y2, kdf, salt);
+        registration.compromised = compromised;
+
+        let mut statement = connection.prepare(
+            "SELECT r1, r2, c, s FROM commitments
+             WHERE group_id = ?1 AND identifier = ?2 ORDER BY position",
+        )?;
+        let commitments = statement.query_map(params![self.group_id, identifier], |row| {
+            Ok(SeenCommitment {
+                r1: row.get(0)?,
+                r2: row.get(1)?,
+                c: row.get(2)?,
+                s: row.get(3)?,
+            })
+        })?;
+        for seen in commitments {
+            registration.commitments.push_back(seen?);
+        }
+
+        Ok(Some(registration))
+    }
+
+    // replaces the row of an existing registration, keeping registered_at
+    fn write(
+        &self,
+        transaction: &Transaction,
+        registration: &Registration,
+    ) -> Result<(), StoreError> {
+        transaction.execute(
+            "UPDATE registrations
+             SET y1 = ?3, y2 = ?4, kdf = ?5, salt = ?6, compromised = ?7, updated_at = ?8
+             WHERE group_id = ?1 AND identifier = ?2",
+            params![
+                self.group_id,
+                registration.identifier,
+                registration.y1,
+                registration.y2,
+                kdf_to_spec(&registration.kdf),
+                registration.salt,
+                registration.compromised,
+                unix_time(),
+            ],
+        )?;
+        self.write_commitments(transaction, registration)
+    }
+
+    fn insert(
+        &self,
+        transaction: &Transaction,
+        registration: &Registration,
+    ) -> Result<(), StoreError> {
+        let now = unix_time();
+        transaction.execute(
+            "INSERT INTO registrations
+             (group_id, identifier, y1, y2, kdf, salt, compromised, registered_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+            params![
+                self.group_id,
+                registration.identifier,
+                registration.y1,
+                registration.y2,
+                kdf_to_spec(&registration.kdf),
+                registration.salt,
+                registration.compromised,
+                now,
+            ],
+        )?;
+        self.write_commitments(transaction, registration)
+    }
+
+    fn write_commitments(
+        &self,
+        transaction: &Transaction,
+        registration: &Registration,
+    ) -> Result<(), StoreError> {
+        transaction.execute(
+            "DELETE FROM commitments WHERE group_id = ?1 AND identifier = ?2",
+            params![self.group_id, registration.identifier],
+        )?;
+
+        let mut statement = transaction.prepare(
+            "INSERT INTO commitments (group_id, identifier, position, r1, r2, c, s)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for (position, seen) in registration.commitments.iter().enumerate() {
+            statement.execute(params![
+                self.group_id,
+                registration.identifier,
+                position,
+                seen.r1,
+                seen.r2,
+                seen.c,
+                seen.s,
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    fn delete_in(&self, transaction: &Transaction, identifier: &str) -> Result<bool, StoreError> {
+        let deleted = transaction.execute(
+            "DELETE FROM registrations WHERE group_id = ?1 AND identifier = ?2",
+            params![self.group_id, identifier],
+        )?;
+        Ok(deleted > 0)
+    }
+}
+
+impl CredentialStore for SqliteStore {
+    fn get(&self, identifier: &str) -> Result<Option<Registration>, StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        self.read(connection, identifier)
+    }
+
+    fn put(&self, registration: Registration) -> Result<(), StoreError> {
+        let connection = &mut self.connection.lock().unwrap();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        self.delete_in(&transaction, &registration.identifier)?;
+        self.insert(&transaction, &registration)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn delete(&self, identifier: &str) -> Result<bool, StoreError> {
+        let connection = &mut self.connection.lock().unwrap();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let deleted = self.delete_in(&transaction, identifier)?;
+        transaction.commit()?;
+        Ok(deleted)
+    }
+
+    fn list(&self) -> Result<Vec<Registration>, StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT identifier FROM registrations WHERE group_id = ?1 ORDER BY identifier",
+        )?;
+        let identifiers = statement
+            .query_map(params![self.group_id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut registrations = Vec::with_capacity(identifiers.len());
+        for identifier in identifiers {
+            registrations.extend(self.read(connection, &identifier)?);
+        }
+        Ok(registrations)
+    }
+
+    fn compare_and_set(
+        &self,
+        expected: Option<&Registration>,
+        new: Registration,
+    ) -> Result<bool, StoreError> {
+        let connection = &mut self.connection.lock().unwrap();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let current = self.read(&transaction, &new.identifier)?;
+        if current.as_ref() != expected {
+            return Ok(false);
+        }
+        match current {
+            Some(_) => self.write(&transaction, &new)?,
+            None => self.insert(&transaction, &new)?,
+        }
+
+        transaction.commit()?;
+        Ok(true)
+    }
+}
+
+impl SessionStore for SqliteStore {
+    fn put_session(&self, session: Session) -> Result<(), StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT OR REPLACE INTO sessions
+             (session_hash, group_id, identifier, created_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                session_hash(&session.session_id),
+                self.group_id,
+                session.identifier,
+                session.created_at,
+                session.expires_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_session(&self, session_id: &str, now: u64) -> Result<Option<Session>, StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        let session = connection
+            .query_row(
+                "SELECT identifier, created_at, expires_at FROM sessions
+                 WHERE session_hash = ?1 AND group_id = ?2 AND expires_at > ?3",
+                params![session_hash(session_id), self.group_id, now],
+                |row| {
+                    Ok(Session {
+                        session_id: session_id.to_string(),
+                        identifier: row.get(0)?,
+                        created_at: row.get(1)?,
+                        expires_at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(session)
+    }
+
+    fn refresh_session(
+        &self,
+        session_id: &str,
+        now: u64,
+        expires_at: u64,
+    ) -> Result<Option<Session>, StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        let session = connection
+            .query_row(
+                "UPDATE sessions SET expires_at = ?4
+                 WHERE session_hash = ?1 AND group_id = ?2 AND expires_at > ?3
+                 RETURNING identifier, created_at, expires_at",
+                params![session_hash(session_id), self.group_id, now, expires_at],
+                |row| {
+                    Ok(Session {
+                        session_id: session_id.to_string(),
+                        identifier: row.get(0)?,
+                        created_at: row.get(1)?,
+                        expires_at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(session)
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<bool, StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        let deleted = connection.execute(
+            "DELETE FROM sessions WHERE session_hash = ?1 AND group_id = ?2",
+            params![session_hash(session_id), self.group_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    fn delete_sessions_of(&self, identifier: &str) -> Result<usize, StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        let deleted = connection.execute(
+            "DELETE FROM sessions WHERE group_id = ?1 AND identifier = ?2",
+            params![self.group_id, identifier],
+        )?;
+        Ok(deleted)
+    }
+
+    fn delete_expired_sessions(&self, now: u64) -> Result<usize, StoreError> {
+        let connection = &self.connection.lock().unwrap();
+        let deleted = connection.execute(
+            "DELETE FROM sessions WHERE group_id = ?1 AND expires_at <= ?2",
+            params![self.group_id, now],
+        )?;
+        Ok(deleted)
+    }
+}
+
+// each step reads the version inside its transaction, so two processes opening a fresh
+// database never apply a migration twice
+fn migrate(connection: &mut Connection) -> Result<(), StoreError> {
+    loop {
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let version: usize =
+            transaction.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        let Some(migration) = MIGRATIONS.get(version) else {
+            if version > MIGRATIONS.len() {
+                return Err(StoreError::Corrupt(format!(
+                    "schema version {} is newer than this verifier ({})",
+                    version,
+                    MIGRATIONS.len()
+                )));
+            }
+            return Ok(());
+        };
+
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", version + 1)?;
+        transaction.commit()?;
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the unix epoch")
+        .as_secs()
+}
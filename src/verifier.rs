@@ -1,59 +1,500 @@
-use num_bigint::BigUint;
-use std::{collections::HashMap, sync::Mutex};
+use hmac::{Hmac, Mac};
+use rand_core::OsRng;
+use sha2::Sha256;
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tonic::{Code, Request, Response, Status, transport::Server};
 
-use cp_protocol::Proof;
+use cp_protocol::{
+    Backend, BoxedRng, Error, Group, Kdf, MinimumStrength, ParameterSet, Proof, Ristretto255,
+    kdf::SALT_LENGTH,
+    store::{
+        CredentialStore, FileStore, MemoryStore, Registration, Session, SessionStore, StoreError,
+    },
+};
+
+use cp_protocol::auth::{
+    self,
+    auth_server::{Auth, AuthServer},
+};
+
+use cp_protocol::auth::{
+    AuthenticationAnswerResponse, AuthenticationChallengeResponse, LoginResponse, RegisterResponse,
+};
+
+// how far a login timestamp may drift from the verifier clock, in seconds
+const LOGIN_WINDOW_SECS: u64 = 60;
+
+// how long a session lasts unless refreshed, in seconds, unless --session-ttl=<secs> says otherwise
+const DEFAULT_SESSION_TTL_SECS: u64 = 3600;
+
+// session ids are bearer tokens, alphanumeric, about 190 bits
+const SESSION_ID_LENGTH: usize = 32;
 
-pub mod auth {
-    include!("./auth.rs");
+// how long a challenge may be answered, in seconds, unless --challenge-ttl=<secs> says otherwise
+const DEFAULT_CHALLENGE_TTL_SECS: u64 = 60;
+
+pub struct AuthImpl<G: Group, S: CredentialStore = MemoryStore> {
+    pub proof: Proof<G>,
+    // registrations, with the commitments each user has sent
+    pub store: S,
+    // challenges handed out and not answered yet, by auth_id. each login attempt has its
+    // own, so two devices of one user never overwrite each other's
+    pub pending: Mutex<HashMap<String, PendingChallenge<G>>>,
+    // auth_ids that can no longer be answered, see SpentChallenge
+    pub spent: Mutex<HashMap<String, SpentChallenge>>,
+    // (identifier, r1) of every challenge handed out in the last two ttls, with the time it
+    // was handed out. covers commitments waiting for an answer and answered ones on their
+    // way to the store, which only keeps verified answers
+    pub recent_commitments: Mutex<HashMap<(String, Vec<u8>), u64>>,
+    pub challenge_ttl: u64,
+    pub session_ttl: u64,
+    pub metrics: Metrics,
+    // r1 of every accepted login proof still inside the window, with its timestamp
+    pub used_logins: Mutex<HashMap<Vec<u8>, u64>>,
+    // draws challenges and identifiers
+    pub rng: Mutex<BoxedRng>,
+    // keys the salts made up for unknown users, see fake_kdf_parameters
+    pub salt_key: [u8; 32],
 }
 
-use auth::auth_server::{Auth, AuthServer};
+#[derive(Debug)]
+pub struct PendingChallenge<G: Group> {
+    pub identifier: String,
+    pub r1: G::Element,
+    // None for Schnorr commitments
+    pub r2: Option<G::Element>,
+    pub c: G::Scalar,
+    // unix seconds
+    pub created_at: u64,
+}
 
-use crate::auth::{
-    AuthenticationAnswerResponse, AuthenticationChallengeResponse, RegisterResponse,
-};
+// a challenge that was answered or expired, kept for one more ttl so that a replay or a late
+// answer is told apart from an auth_id that never existed
+#[derive(Debug, Clone, Copy)]
+pub struct SpentChallenge {
+    pub answered: bool,
+    // unix seconds
+    pub forget_at: u64,
+}
 
-#[derive(Debug, Default)]
-pub struct AuthImpl {
-    pub user_info: Mutex<HashMap<String, UserInfo>>,
-    pub auth_id_to_user: Mutex<HashMap<String, String>>,
+// why an auth_id cannot be answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unanswerable {
+    Unknown,
+    Answered,
+    Expired,
 }
 
+// logged by the garbage collector on every sweep
 #[derive(Debug, Default)]
-pub struct UserInfo {
-    // registration
+pub struct Metrics {
+    // answers to a challenge past its ttl
+    pub expired_attempts: AtomicU64,
+    // answers to a challenge that was already answered
+    pub replayed_attempts: AtomicU64,
+    // challenges nobody answered before they expired
+    pub collected_challenges: AtomicU64,
+}
+
+// a registration decoded into the verifier's group
+pub struct Credentials<G: Group> {
     pub identifier: String,
-    pub y1: BigUint,
-    pub y2: BigUint,
-    // authorization
-    pub r1: BigUint,
-    pub r2: BigUint,
-    // verification
-    pub c: BigUint,
-    pub s: BigUint,
-    pub session_id: String,
+    pub y1: G::Element,
+    // None for single-base (Schnorr) credentials
+    pub y2: Option<G::Element>,
+}
+
+impl<G: Group> AuthImpl<G> {
+    pub fn new(proof: Proof<G>) -> Self {
+        AuthImpl::with_rng(proof, Box::new(OsRng))
+    }
+
+    pub fn with_rng(proof: Proof<G>, rng: BoxedRng) -> Self {
+        AuthImpl::with_store(proof, MemoryStore::new(), rng)
+    }
+}
+
+impl<G: Group, S: CredentialStore + SessionStore> AuthImpl<G, S> {
+    pub fn with_store(proof: Proof<G>, store: S, mut rng: BoxedRng) -> Self {
+        let mut salt_key = [0u8; 32];
+        rng.fill_bytes(&mut salt_key);
+
+        AuthImpl {
+            proof,
+            store,
+            pending: Mutex::new(HashMap::new()),
+            spent: Mutex::new(HashMap::new()),
+            recent_commitments: Mutex::new(HashMap::new()),
+            challenge_ttl: DEFAULT_CHALLENGE_TTL_SECS,
+            session_ttl: DEFAULT_SESSION_TTL_SECS,
+            metrics: Metrics::default(),
+            used_logins: Mutex::new(HashMap::new()),
+            rng: Mutex::new(rng),
+            salt_key,
+        }
+    }
+
+    pub fn with_challenge_ttl(mut self, secs: u64) -> Self {
+        self.challenge_ttl = secs;
+        self
+    }
+
+    pub fn with_session_ttl(mut self, secs: u64) -> Self {
+        self.session_ttl = secs;
+        self
+    }
+
+    // what an unknown user gets from GetKdfParameters, so the answer does not tell which
+    // identifiers are registered. the salt is the same on every call for one identifier
+    fn fake_kdf_parameters(&self, identifier: &str) -> auth::KdfParameters {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.salt_key).expect("HMAC accepts any key length");
+        mac.update(identifier.as_bytes());
+        let salt = mac.finalize().into_bytes();
+        Kdf::default().to_proto(&salt[..SALT_LENGTH])
+    }
+
+    fn issue_session(&self, identifier: &str) -> Result<Session, StoreError> {
+        let session_id = {
+            let rng = &mut **self.rng.lock().unwrap();
+            Proof::generate_random_string_below_with_rng(SESSION_ID_LENGTH, rng)
+        };
+        let now = unix_time();
+        let session = Session {
+            session_id,
+            identifier: identifier.to_string(),
+            created_at: now,
+            expires_at: now.saturating_add(self.session_ttl),
+        };
+
+        self.store.put_session(session.clone())?;
+        Ok(session)
+    }
+
+    // takes the challenge out of pending, so each auth_id gets exactly one attempt whether
+    // the answer turns out right or wrong
+    fn consume_challenge(
+        &self,
+        auth_id: &str,
+        now: u64,
+    ) -> Result<PendingChallenge<G>, Unanswerable> {
+        let pending = &mut self.pending.lock().unwrap();
+        let spent = &mut self.spent.lock().unwrap();
+
+        let answered = match pending.remove(auth_id) {
+            Some(challenge) => {
+                let expired = now > challenge.created_at.saturating_add(self.challenge_ttl);
+                spent.insert(
+                    auth_id.to_string(),
+                    SpentChallenge {
+                        answered: !expired,
+                        forget_at: now.saturating_add(self.challenge_ttl),
+                    },
+                );
+                if !expired {
+                    return Ok(challenge);
+                }
+                false
+            }
+            None => match spent.get(auth_id) {
+                Some(spent) => spent.answered,
+                None => return Err(Unanswerable::Unknown),
+            },
+        };
+
+        if answered {
+            self.metrics
+                .replayed_attempts
+                .fetch_add(1, Ordering::Relaxed);
+            Err(Unanswerable::Answered)
+        } else {
+            self.metrics
+                .expired_attempts
+                .fetch_add(1, Ordering::Relaxed);
+            Err(Unanswerable::Expired)
+        }
+    }
+
+    // moves expired challenges to spent, forgets spent ones past forget_at and ends expired
+    // sessions
+    fn collect_garbage(&self, now: u64) {
+        match self.store.delete_expired_sessions(now) {
+            Ok(0) => {}
+            Ok(ended) => println!("Ended {} expired sessions", ended),
+            Err(error) => println!("Could not end expired sessions: {}", error),
+        }
+
+        let pending = &mut self.pending.lock().unwrap();
+        let spent = &mut self.spent.lock().unwrap();
+
+        let before = pending.len();
+        pending.retain(|auth_id, challenge| {
+            if now <= challenge.created_at.saturating_add(self.challenge_ttl) {
+                return true;
+            }
+            spent.insert(
+                auth_id.clone(),
+                SpentChallenge {
+                    answered: false,
+                    forget_at: now.saturating_add(self.challenge_ttl),
+                },
+            );
+            false
+        });
+        spent.retain(|_, spent| now <= spent.forget_at);
+        let recent = &mut self.recent_commitments.lock().unwrap();
+        recent.retain(|_, handed_out_at| {
+            now <= handed_out_at.saturating_add(self.challenge_ttl.saturating_mul(2))
+        });
+
+        // logged on every sweep, so a quiet verifier still shows it is sweeping
+        let collected = (before - pending.len()) as u64;
+        let metrics = &self.metrics;
+        let total = metrics
+            .collected_challenges
+            .fetch_add(collected, Ordering::Relaxed);
+        println!(
+            "Collected {} expired challenges ({} in total, {} pending, {} expired and {} replayed answers)",
+            collected,
+            total + collected,
+            pending.len(),
+            metrics.expired_attempts.load(Ordering::Relaxed),
+            metrics.replayed_attempts.load(Ordering::Relaxed),
+        );
+    }
+
+    fn generate_challenge(&self) -> G::Scalar {
+        let rng = &mut **self.rng.lock().unwrap();
+        self.proof.generate_random_number_with_rng(rng)
+    }
+
+    fn generate_id(&self) -> String {
+        let rng = &mut **self.rng.lock().unwrap();
+        Proof::generate_random_string_below_with_rng(12, rng)
+    }
+
+    // the store only holds elements checked at registration, so they are decoded without
+    // another membership test. verify checks y1 and y2 anyway
+    fn credentials(&self, registration: &Registration) -> Result<Credentials<G>, Error> {
+        let group = self.proof.group();
+        let decode = |name, bytes: &[u8]| group.decode_element(bytes).ok_or(Error::Encoding(name));
+
+        Ok(Credentials {
+            identifier: registration.identifier.clone(),
+            y1: decode("y1", &registration.y1)?,
+            y2: match &registration.y2 {
+                Some(y2) => Some(decode("y2", y2)?),
+                None => None,
+            },
+        })
+    }
+
+    // records a verified answer, see Registration::record_transcript. Some(true) if this
+    // flagged the account, None if the user is gone
+    fn record_transcript(
+        &self,
+        identifier: &str,
+        r1: &G::Element,
+        r2: Option<&G::Element>,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<Option<bool>, StoreError> {
+        let group = self.proof.group();
+        let (r1, r2) = (
+            group.encode_element(r1),
+            r2.map(|r2| group.encode_element(r2)).unwrap_or_default(),
+        );
+        let (c, s) = (group.encode_scalar(c), group.encode_scalar(s));
+
+        let compromised = self.store.update(identifier, |registration| {
+            let was_compromised = registration.compromised;
+            registration.record_transcript(r1.clone(), r2.clone(), c.clone(), s.clone());
+            !was_compromised && registration.compromised
+        })?;
+
+        if compromised == Some(true) {
+            println!(
+                "User {} reused a commitment for two challenges, secret is exposed",
+                identifier
+            );
+            // whoever holds x could have opened them
+            self.store.delete_sessions_of(identifier)?;
+        }
+
+        Ok(compromised)
+    }
+
+    // r2 is sent exactly when the user registered y2
+    fn decode_r2(
+        &self,
+        credentials: &Credentials<G>,
+        bytes: &[u8],
+    ) -> Result<Option<G::Element>, Error> {
+        match (&credentials.y2, bytes.is_empty()) {
+            (Some(_), _) => self.proof.decode_element("r2", bytes).map(Some),
+            (None, true) => Ok(None),
+            (None, false) => Err(Error::Encoding("r2")),
+        }
+    }
+
+    // Chaum-Pedersen for users with y2, Schnorr on y1 alone otherwise. a missing r2
+    // never downgrades a Chaum-Pedersen user
+    fn verify_answer(
+        &self,
+        credentials: &Credentials<G>,
+        r1: &G::Element,
+        r2: Option<&G::Element>,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<(), Error> {
+        match (&credentials.y2, r2) {
+            (Some(y2), Some(r2)) => self.proof.verify(r1, r2, &credentials.y1, y2, c, s),
+            (None, None) => self.proof.schnorr().verify(r1, &credentials.y1, c, s),
+            _ => Err(Error::Encoding("r2")),
+        }
+    }
+
+    // the Fiat-Shamir challenge a login proof answers, see verify_answer
+    fn login_challenge(
+        &self,
+        credentials: &Credentials<G>,
+        r1: &G::Element,
+        r2: Option<&G::Element>,
+        nonce: &[u8],
+    ) -> Result<G::Scalar, Error> {
+        let (identifier, y1) = (&credentials.identifier, &credentials.y1);
+        match (&credentials.y2, r2) {
+            (Some(y2), Some(r2)) => Ok(self.proof.challenge(y1, y2, r1, r2, identifier, nonce)),
+            (None, None) => Ok(self.proof.schnorr().challenge(y1, r1, identifier, nonce)),
+            _ => Err(Error::Encoding("r2")),
+        }
+    }
+}
+
+// malformed input is the client's fault, a proof that does not check out is a failed login
+fn error_status(error: Error) -> Status {
+    let code = match error {
+        Error::InvalidElement(_) | Error::ScalarOutOfRange(_) | Error::Encoding(_) => {
+            Code::InvalidArgument
+        }
+        Error::VerificationFailed(_) => Code::Unauthenticated,
+        Error::InvalidParameters(_) => Code::Internal,
+    };
+
+    Status::new(code, error.to_string())
+}
+
+fn compromised_account() -> Status {
+    Status::new(
+        Code::PermissionDenied,
+        "Account is flagged as compromised, register again",
+    )
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the unix epoch")
+        .as_secs()
+}
+
+// the store failing is the verifier's fault, the client may retry
+fn store_status(error: StoreError) -> Status {
+    Status::new(Code::Internal, error.to_string())
+}
+
+fn unanswerable_status(reason: Unanswerable) -> Status {
+    match reason {
+        Unanswerable::Unknown => Status::new(Code::NotFound, "Auth ID not found"),
+        Unanswerable::Answered => Status::new(
+            Code::FailedPrecondition,
+            "Challenge was already answered, request a new one",
+        ),
+        Unanswerable::Expired => Status::new(
+            Code::DeadlineExceeded,
+            "Challenge expired, request a new one",
+        ),
+    }
+}
+
+fn invalid_session() -> Status {
+    Status::new(Code::Unauthenticated, "Session expired or unknown")
+}
+
+fn user_not_found(identifier: &str) -> Status {
+    Status::new(Code::NotFound, format!("User: {} not found", identifier))
 }
 
 #[tonic::async_trait]
-impl Auth for AuthImpl {
+impl<G, S> Auth for AuthImpl<G, S>
+where
+    G: Group + Send + Sync + 'static,
+    G::Element: Send + Sync,
+    G::Scalar: Send + Sync,
+    G::FixedBase: Send + Sync,
+    S: CredentialStore + SessionStore + 'static,
+{
     async fn register(
         &self,
         request: Request<auth::RegisterRequest>,
     ) -> Result<Response<auth::RegisterResponse>, Status> {
         let request = request.into_inner();
+        let group = self.proof.group();
 
         let identifier = request.identifier;
+        // the verifier never runs the kdf, it only stores what the prover registered
+        let kdf = request
+            .kdf
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "Missing kdf parameters"))?;
+        let (kdf, salt) = Kdf::from_proto(kdf)
+            .map_err(|error| Status::new(Code::InvalidArgument, error.to_string()))?;
 
-        let user_info = UserInfo {
-            identifier: identifier.clone(),
-            y1: BigUint::from_bytes_be(&request.y1),
-            y2: BigUint::from_bytes_be(&request.y2),
-            ..Default::default()
+        let protocol = auth::Protocol::from_i32(request.protocol)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "Unknown protocol"))?;
+        let y2 = match protocol {
+            auth::Protocol::ChaumPedersen => Some(
+                self.proof
+                    .decode_element("y2", &request.y2)
+                    .map_err(error_status)?,
+            ),
+            auth::Protocol::Schnorr if request.y2.is_empty() => None,
+            auth::Protocol::Schnorr => {
+                return Err(Status::new(
+                    Code::InvalidArgument,
+                    "Schnorr credentials take no y2",
+                ));
+            }
         };
+        let y1 = self
+            .proof
+            .decode_element("y1", &request.y1)
+            .map_err(error_status)?;
 
-        let user_info_hashmap = &mut self.user_info.lock().unwrap();
-        user_info_hashmap.insert(identifier, user_info);
+        let registration = Registration::new(
+            identifier.clone(),
+            group.encode_element(&y1),
+            y2.map(|y2| group.encode_element(&y2)),
+            kdf,
+            salt,
+        );
+        self.store.put(registration).map_err(store_status)?;
+        // sessions opened with the old credentials end with them
+        self.store
+            .delete_sessions_of(&identifier)
+            .map_err(store_status)?;
+
+        // challenges handed out for the old credentials are void
+        let pending = &mut self.pending.lock().unwrap();
+        pending.retain(|_, challenge| challenge.identifier != identifier);
+        let recent = &mut self.recent_commitments.lock().unwrap();
+        recent.retain(|(user, _), _| *user != identifier);
 
         Ok(Response::new(RegisterResponse {}))
     }
@@ -65,30 +506,71 @@ impl Auth for AuthImpl {
         let request = request.into_inner();
         let identifier = request.identifier;
 
-        let user_info_hashmap = &mut self.user_info.lock().unwrap();
+        let group = self.proof.group();
 
-        if let Some(user_info) = user_info_hashmap.get_mut(&identifier) {
-            user_info.r1 = BigUint::from_bytes_be(&request.r1);
-            user_info.r2 = BigUint::from_bytes_be(&request.r2);
+        let r1 = self
+            .proof
+            .decode_element("r1", &request.r1)
+            .map_err(error_status)?;
 
-            let (_, _, _, q) = Proof::get_constants();
-            let c = Proof::generate_random_number_below(&q);
-            user_info.c = c.clone();
+        let registration = self
+            .store
+            .get(&identifier)
+            .map_err(store_status)?
+            .ok_or_else(|| user_not_found(&identifier))?;
 
-            let auth_id = Proof::generate_random_string_below(12);
-            let auth_id_to_user = &mut self.auth_id_to_user.lock().unwrap();
-            auth_id_to_user.insert(auth_id.clone(), identifier);
+        if registration.compromised {
+            return Err(compromised_account());
+        }
 
-            return Ok(Response::new(AuthenticationChallengeResponse {
-                auth_id,
-                c: c.to_bytes_be(),
-            }));
-        } else {
+        let credentials = self.credentials(&registration).map_err(error_status)?;
+        let r2 = self
+            .decode_r2(&credentials, &request.r2)
+            .map_err(error_status)?;
+
+        // answering two challenges with one commitment reveals x, never hand out a second.
+        // answered commitments are in the store, recent ones in recent_commitments
+        let encoded_r1 = group.encode_element(&r1);
+        let encoded_r2 = r2.as_ref().map(|r2| group.encode_element(r2));
+        let encoded_r2 = encoded_r2.unwrap_or_default();
+        let now = unix_time();
+        let fresh = !registration.seen_commitment(&encoded_r1, &encoded_r2) && {
+            let recent = &mut self.recent_commitments.lock().unwrap();
+            match recent.entry((identifier.clone(), encoded_r1)) {
+                Entry::Occupied(_) => false,
+                Entry::Vacant(entry) => {
+                    entry.insert(now);
+                    true
+                }
+            }
+        };
+        if !fresh {
             return Err(Status::new(
-                Code::NotFound,
-                format!("User: {} not found", identifier),
+                Code::InvalidArgument,
+                "Commitment was already used, draw a fresh nonce",
             ));
         }
+
+        let c = self.generate_challenge();
+        let encoded_c = group.encode_scalar(&c);
+
+        let auth_id = self.generate_id();
+        let pending = &mut self.pending.lock().unwrap();
+        pending.insert(
+            auth_id.clone(),
+            PendingChallenge {
+                identifier,
+                r1,
+                r2,
+                c,
+                created_at: now,
+            },
+        );
+
+        Ok(Response::new(AuthenticationChallengeResponse {
+            auth_id,
+            c: encoded_c,
+        }))
     }
 
     async fn verify_authentication(
@@ -98,52 +580,768 @@ impl Auth for AuthImpl {
         let request = request.into_inner();
         let auth_id = request.auth_id;
 
-        let auth_id_to_user_hashmap = &mut self.auth_id_to_user.lock().unwrap();
+        let PendingChallenge {
+            identifier,
+            r1,
+            r2,
+            c,
+            ..
+        } = self
+            .consume_challenge(&auth_id, unix_time())
+            .map_err(unanswerable_status)?;
 
-        if let Some(identifier) = auth_id_to_user_hashmap.get(&auth_id) {
-            let user_info_hashmap = &mut self.user_info.lock().unwrap();
+        let registration = self
+            .store
+            .get(&identifier)
+            .map_err(store_status)?
+            .ok_or_else(|| user_not_found(&identifier))?;
 
-            let user_info = user_info_hashmap
-                .get(identifier)
-                .expect("User info not found for identifier");
+        if registration.compromised {
+            return Err(compromised_account());
+        }
 
-            let (p, q, alpha, beta) = Proof::get_constants();
+        let s = self
+            .proof
+            .decode_scalar("s", &request.s)
+            .map_err(error_status)?;
 
-            let proof = Proof::new(p, q, alpha, beta);
+        let credentials = self.credentials(&registration).map_err(error_status)?;
+        self.verify_answer(&credentials, &r1, r2.as_ref(), &c, &s)
+            .map_err(error_status)?;
 
-            let s = BigUint::from_bytes_be(&request.s);
+        let compromised = self
+            .record_transcript(&identifier, &r1, r2.as_ref(), &c, &s)
+            .map_err(store_status)?
+            .ok_or_else(|| user_not_found(&identifier))?;
 
-            let verification = proof.verify(
-                &user_info.r1,
-                &user_info.r2,
-                &user_info.y1,
-                &user_info.y2,
-                &user_info.c,
-                &s,
-            );
+        if compromised {
+            return Err(compromised_account());
+        }
 
-            if verification {
-                let session_id = Proof::generate_random_string_below(12);
-                return Ok(Response::new(AuthenticationAnswerResponse {
-                    session_id: session_id.clone(),
-                }));
-            } else {
-                return Err(Status::new(Code::Unauthenticated, "Verification failed"));
-            }
-        } else {
-            return Err(Status::new(Code::NotFound, "Auth ID not found"));
+        let session = self.issue_session(&identifier).map_err(store_status)?;
+        Ok(Response::new(AuthenticationAnswerResponse {
+            session_id: session.session_id,
+            expires_at: session.expires_at,
+        }))
+    }
+
+    async fn login(
+        &self,
+        request: Request<auth::LoginRequest>,
+    ) -> Result<Response<auth::LoginResponse>, Status> {
+        let request = request.into_inner();
+        let identifier = request.identifier;
+        let group = self.proof.group();
+
+        let now = unix_time();
+        if request.timestamp.abs_diff(now) > LOGIN_WINDOW_SECS {
+            return Err(Status::new(
+                Code::Unauthenticated,
+                "Login timestamp outside the accepted window",
+            ));
+        }
+
+        let r1 = self
+            .proof
+            .decode_element("r1", &request.r1)
+            .map_err(error_status)?;
+        let s = self
+            .proof
+            .decode_scalar("s", &request.s)
+            .map_err(error_status)?;
+
+        let registration = self
+            .store
+            .get(&identifier)
+            .map_err(store_status)?
+            .ok_or_else(|| user_not_found(&identifier))?;
+
+        if registration.compromised {
+            return Err(compromised_account());
+        }
+
+        let credentials = self.credentials(&registration).map_err(error_status)?;
+        let r2 = self
+            .decode_r2(&credentials, &request.r2)
+            .map_err(error_status)?;
+        let c = self
+            .login_challenge(
+                &credentials,
+                &r1,
+                r2.as_ref(),
+                &request.timestamp.to_be_bytes(),
+            )
+            .map_err(error_status)?;
+        self.verify_answer(&credentials, &r1, r2.as_ref(), &c, &s)
+            .map_err(error_status)?;
+
+        // a replay carries the same challenge, a fresh timestamp over an old commitment does not
+        let compromised = self
+            .record_transcript(&identifier, &r1, r2.as_ref(), &c, &s)
+            .map_err(store_status)?
+            .ok_or_else(|| user_not_found(&identifier))?;
+
+        if compromised {
+            return Err(compromised_account());
         }
+
+        // a valid proof could be captured and sent again while its timestamp is fresh
+        let used_logins = &mut self.used_logins.lock().unwrap();
+        used_logins.retain(|_, timestamp| timestamp.abs_diff(now) <= LOGIN_WINDOW_SECS);
+
+        if used_logins
+            .insert(group.encode_element(&r1), request.timestamp)
+            .is_some()
+        {
+            return Err(Status::new(
+                Code::Unauthenticated,
+                "Login proof already used",
+            ));
+        }
+
+        let session = self.issue_session(&identifier).map_err(store_status)?;
+        Ok(Response::new(LoginResponse {
+            session_id: session.session_id,
+            expires_at: session.expires_at,
+        }))
+    }
+
+    async fn get_kdf_parameters(
+        &self,
+        request: Request<auth::KdfParametersRequest>,
+    ) -> Result<Response<auth::KdfParameters>, Status> {
+        let identifier = request.into_inner().identifier;
+
+        let parameters = match self.store.get(&identifier).map_err(store_status)? {
+            Some(registration) => registration.kdf.to_proto(&registration.salt),
+            None => self.fake_kdf_parameters(&identifier),
+        };
+        Ok(Response::new(parameters))
+    }
+
+    async fn validate_session(
+        &self,
+        request: Request<auth::ValidateSessionRequest>,
+    ) -> Result<Response<auth::ValidateSessionResponse>, Status> {
+        let session_id = request.into_inner().session_id;
+
+        let Some(session) = self
+            .store
+            .get_session(&session_id, unix_time())
+            .map_err(store_status)?
+        else {
+            return Err(invalid_session());
+        };
+
+        // a user removed from the store or flagged since keeps no session
+        match self.store.get(&session.identifier).map_err(store_status)? {
+            Some(registration) if !registration.compromised => {}
+            _ => return Err(invalid_session()),
+        }
+
+        Ok(Response::new(auth::ValidateSessionResponse {
+            identifier: session.identifier,
+            created_at: session.created_at,
+            expires_at: session.expires_at,
+        }))
+    }
+
+    async fn refresh_session(
+        &self,
+        request: Request<auth::RefreshSessionRequest>,
+    ) -> Result<Response<auth::RefreshSessionResponse>, Status> {
+        let session_id = request.into_inner().session_id;
+
+        let now = unix_time();
+        match self
+            .store
+            .refresh_session(&session_id, now, now.saturating_add(self.session_ttl))
+            .map_err(store_status)?
+        {
+            Some(session) => Ok(Response::new(auth::RefreshSessionResponse {
+                expires_at: session.expires_at,
+            })),
+            None => Err(invalid_session()),
+        }
+    }
+
+    // ending a session that already ended is not an error
+    async fn logout(
+        &self,
+        request: Request<auth::LogoutRequest>,
+    ) -> Result<Response<auth::LogoutResponse>, Status> {
+        let session_id = request.into_inner().session_id;
+        self.store
+            .delete_session(&session_id)
+            .map_err(store_status)?;
+
+        Ok(Response::new(auth::LogoutResponse {}))
     }
 }
 
-#[tokio::main]
-async fn main() {
-    let addr = "127.0.0.1:50051".to_string();
-    println!("Verifier listening on {}", addr);
+async fn serve<G, S>(addr: &str, auth: AuthImpl<G, S>)
+where
+    G: Group + Send + Sync + 'static,
+    G::Element: Send + Sync,
+    G::Scalar: Send + Sync,
+    G::FixedBase: Send + Sync,
+    S: CredentialStore + SessionStore + 'static,
+{
+    // --challenge-ttl=<secs> bounds how long a challenge may be answered
+    let ttl = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--challenge-ttl=").map(str::to_string))
+        .map(|secs| secs.parse().expect("Could not parse the challenge ttl"))
+        .unwrap_or(DEFAULT_CHALLENGE_TTL_SECS);
+    // --session-ttl=<secs> bounds how long a session lasts between refreshes
+    let session_ttl = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--session-ttl=").map(str::to_string))
+        .map(|secs| secs.parse().expect("Could not parse the session ttl"))
+        .unwrap_or(DEFAULT_SESSION_TTL_SECS);
+    let auth = Arc::new(auth.with_challenge_ttl(ttl).with_session_ttl(session_ttl));
+
+    // expired challenges are swept every ttl, so none outlives it by more than twice
+    let collector = auth.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(ttl.max(1)));
+        loop {
+            interval.tick().await;
+            collector.collect_garbage(unix_time());
+        }
+    });
 
     Server::builder()
-        .add_service(AuthServer::new(AuthImpl::default()))
+        .add_service(AuthServer::from_arc(auth))
         .serve(addr.parse().expect("Could not parse address"))
         .await
         .unwrap();
 }
+
+// --store=<path> keeps registrations in a file across restarts, --sqlite=<path> in a SQLite
+// database under group_id, in memory otherwise
+#[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+async fn serve_with_store<G>(addr: &str, proof: Proof<G>, rng: BoxedRng, group_id: &str)
+where
+    G: Group + Send + Sync + 'static,
+    G::Element: Send + Sync,
+    G::Scalar: Send + Sync,
+    G::FixedBase: Send + Sync,
+{
+    #[cfg(feature = "sqlite")]
+    if let Some(path) =
+        std::env::args().find_map(|arg| arg.strip_prefix("--sqlite=").map(str::to_string))
+    {
+        let store = cp_protocol::store::sqlite::SqliteStore::open(&path, group_id)
+            .expect("Could not open the credential database");
+        let registrations = store
+            .list()
+            .expect("Could not read the credential database");
+        println!(
+            "Loaded {} {} registrations from {}",
+            registrations.len(),
+            group_id,
+            path
+        );
+        return serve(addr, AuthImpl::with_store(proof, store, rng)).await;
+    }
+    match std::env::args().find_map(|arg| arg.strip_prefix("--store=").map(str::to_string)) {
+        Some(path) => {
+            let store = FileStore::open(&path).expect("Could not open the credential store");
+            let registrations = store.list().expect("Could not read the credential store");
+            println!("Loaded {} registrations from {}", registrations.len(), path);
+            serve(addr, AuthImpl::with_store(proof, store, rng)).await;
+        }
+        None => serve(addr, AuthImpl::with_rng(proof, rng)).await,
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let backend = Backend::from_args();
+    // whoever predicts the challenges can forge proofs and whoever predicts the session ids
+    // can take sessions over, so the verifier never runs on a seed. tests seed it through
+    // AuthImpl::with_rng
+    let rng: BoxedRng = Box::new(OsRng);
+    let addr = "127.0.0.1:50051".to_string();
+    println!("Verifier listening on {} ({:?} backend)", addr, backend);
+
+    match backend {
+        Backend::Modp => {
+            let params = ParameterSet::from_args();
+            println!("Using parameter set {} ({})", params.name, params.reference);
+            let proof = params
+                .proof(&MinimumStrength::default())
+                .expect("Invalid group parameters")
+                .with_fixed_base_tables();
+            let group_id = format!("modp/{}", params.name);
+            serve_with_store(&addr, proof, rng, &group_id).await;
+        }
+        Backend::Ristretto => {
+            let (alpha, beta) = Ristretto255::get_constants();
+            let proof = Proof::with_group(Ristretto255, alpha, beta).with_fixed_base_tables();
+            serve_with_store(&addr, proof, rng, "ristretto255").await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cp_protocol::SecretScalar;
+    use cp_protocol::auth::{
+        AuthenticationAnswerRequest, AuthenticationChallengeRequest, KdfParametersRequest,
+        LogoutRequest, RefreshSessionRequest, RegisterRequest, ValidateSessionRequest,
+    };
+    use cp_protocol::store::MAX_COMMITMENTS;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    const TTL: u64 = 60;
+
+    fn verifier() -> AuthImpl<Ristretto255> {
+        let (alpha, beta) = Ristretto255::get_constants();
+        AuthImpl::new(Proof::with_group(Ristretto255, alpha, beta)).with_challenge_ttl(TTL)
+    }
+
+    // stores the credentials of a fresh x under identifier and returns x
+    fn register(auth: &AuthImpl<Ristretto255>, identifier: &str) -> SecretScalar<Ristretto255> {
+        let group = auth.proof.group();
+        let x = SecretScalar::new(auth.proof.generate_random_number());
+        let [y1, y2] = auth.proof.create_pair(&x).unwrap();
+        let registration = Registration::new(
+            identifier.to_string(),
+            group.encode_element(&y1),
+            Some(group.encode_element(&y2)),
+            Kdf::default(),
+            vec![0; 16],
+        );
+        auth.store.put(registration).unwrap();
+        x
+    }
+
+    // a challenge created at the given time, as create_authentication_challenge would
+    fn pending_at(auth: &AuthImpl<Ristretto255>, auth_id: &str, created_at: u64) {
+        let x = SecretScalar::new(auth.proof.generate_random_number());
+        let [r1, r2] = auth.proof.create_pair(&x).unwrap();
+        auth.pending.lock().unwrap().insert(
+            auth_id.to_string(),
+            PendingChallenge {
+                identifier: "alice".to_string(),
+                r1,
+                r2: Some(r2),
+                c: auth.generate_challenge(),
+                created_at,
+            },
+        );
+    }
+
+    fn consume(auth: &AuthImpl<Ristretto255>, auth_id: &str, now: u64) -> Result<(), Unanswerable> {
+        auth.consume_challenge(auth_id, now).map(|_| ())
+    }
+
+    fn count(metric: &AtomicU64) -> u64 {
+        metric.load(Ordering::Relaxed)
+    }
+
+    // r1 and r2 of a random k
+    fn random_commitment(auth: &AuthImpl<Ristretto255>) -> [<Ristretto255 as Group>::Element; 2] {
+        let k = SecretScalar::new(auth.proof.generate_random_number());
+        auth.proof.create_pair(&k).unwrap()
+    }
+
+    async fn ask(
+        auth: &AuthImpl<Ristretto255>,
+        identifier: &str,
+        r1: &<Ristretto255 as Group>::Element,
+        r2: &<Ristretto255 as Group>::Element,
+    ) -> Result<AuthenticationChallengeResponse, Code> {
+        let group = auth.proof.group();
+        let request = AuthenticationChallengeRequest {
+            identifier: identifier.to_string(),
+            r1: group.encode_element(r1),
+            r2: group.encode_element(r2),
+        };
+        auth.create_authentication_challenge(Request::new(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| status.code())
+    }
+
+    // asks for a challenge on a fresh commitment, returns its auth_id with the right s and
+    // a wrong one
+    async fn challenge(
+        auth: &AuthImpl<Ristretto255>,
+        identifier: &str,
+        x: &SecretScalar<Ristretto255>,
+    ) -> (String, Vec<u8>, Vec<u8>) {
+        let group = auth.proof.group();
+        let commitment = auth.proof.prover(x).commit().unwrap();
+        let response = ask(auth, identifier, commitment.r1(), commitment.r2())
+            .await
+            .unwrap();
+
+        let c = group.decode_scalar(&response.c).unwrap();
+        let s = commitment.respond(&c).unwrap();
+        let wrong = group.scalar_add(&s, &group.scalar_from_u128(1));
+        (
+            response.auth_id,
+            group.encode_scalar(&s),
+            group.encode_scalar(&wrong),
+        )
+    }
+
+    async fn answer(
+        auth: &AuthImpl<Ristretto255>,
+        auth_id: &str,
+        s: &[u8],
+    ) -> Result<String, Code> {
+        let request = AuthenticationAnswerRequest {
+            auth_id: auth_id.to_string(),
+            s: s.to_vec(),
+        };
+        auth.verify_authentication(Request::new(request))
+            .await
+            .map(|response| response.into_inner().session_id)
+            .map_err(|status| status.code())
+    }
+
+    // a session from a full login with x
+    async fn login(
+        auth: &AuthImpl<Ristretto255>,
+        identifier: &str,
+        x: &SecretScalar<Ristretto255>,
+    ) -> String {
+        let (auth_id, s, _) = challenge(auth, identifier, x).await;
+        answer(auth, &auth_id, &s).await.unwrap()
+    }
+
+    // the user the session belongs to
+    async fn validate(auth: &AuthImpl<Ristretto255>, session_id: &str) -> Result<String, Code> {
+        let request = ValidateSessionRequest {
+            session_id: session_id.to_string(),
+        };
+        auth.validate_session(Request::new(request))
+            .await
+            .map(|response| response.into_inner().identifier)
+            .map_err(|status| status.code())
+    }
+
+    async fn refresh(auth: &AuthImpl<Ristretto255>, session_id: &str) -> Result<u64, Code> {
+        let request = RefreshSessionRequest {
+            session_id: session_id.to_string(),
+        };
+        auth.refresh_session(Request::new(request))
+            .await
+            .map(|response| response.into_inner().expires_at)
+            .map_err(|status| status.code())
+    }
+
+    async fn logout(auth: &AuthImpl<Ristretto255>, session_id: &str) {
+        let request = LogoutRequest {
+            session_id: session_id.to_string(),
+        };
+        auth.logout(Request::new(request)).await.unwrap();
+    }
+
+    async fn kdf_parameters(
+        auth: &AuthImpl<Ristretto255>,
+        identifier: &str,
+    ) -> auth::KdfParameters {
+        let request = KdfParametersRequest {
+            identifier: identifier.to_string(),
+        };
+        auth.get_kdf_parameters(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_kdf_parameters_of_unknown_users() {
+        let auth = verifier();
+        register(&auth, "alice");
+        let alice = kdf_parameters(&auth, "alice").await;
+        assert_eq!(alice, Kdf::default().to_proto(&[0; 16]));
+
+        // an unknown user looks like a registered one, and the same on every ask
+        let bob = kdf_parameters(&auth, "bob").await;
+        assert_eq!(bob.algorithm, alice.algorithm);
+        assert_eq!(bob.salt.len(), alice.salt.len());
+        assert_eq!(kdf_parameters(&auth, "bob").await, bob);
+        assert_ne!(kdf_parameters(&auth, "carol").await.salt, bob.salt);
+    }
+
+    #[test]
+    fn test_challenge_expires_after_ttl() {
+        let auth = verifier();
+        pending_at(&auth, "on time", 1000);
+        pending_at(&auth, "late", 1000);
+
+        assert!(consume(&auth, "on time", 1000 + TTL).is_ok());
+        assert_eq!(
+            consume(&auth, "late", 1000 + TTL + 1),
+            Err(Unanswerable::Expired)
+        );
+
+        // neither can be tried again, and each refusal is counted
+        assert_eq!(
+            consume(&auth, "on time", 1000 + TTL),
+            Err(Unanswerable::Answered)
+        );
+        assert_eq!(consume(&auth, "late", 1000), Err(Unanswerable::Expired));
+        assert_eq!(
+            consume(&auth, "never issued", 1000),
+            Err(Unanswerable::Unknown)
+        );
+        assert_eq!(count(&auth.metrics.replayed_attempts), 1);
+        assert_eq!(count(&auth.metrics.expired_attempts), 2);
+    }
+
+    #[test]
+    fn test_collect_garbage() {
+        let auth = verifier();
+        pending_at(&auth, "unanswered", 1000);
+
+        auth.collect_garbage(1000 + TTL);
+        assert!(auth.pending.lock().unwrap().contains_key("unanswered"));
+        assert_eq!(count(&auth.metrics.collected_challenges), 0);
+
+        // swept challenges still read as expired rather than unknown
+        auth.collect_garbage(1000 + TTL + 1);
+        assert!(auth.pending.lock().unwrap().is_empty());
+        assert_eq!(count(&auth.metrics.collected_challenges), 1);
+        assert_eq!(
+            consume(&auth, "unanswered", 1000 + TTL + 2),
+            Err(Unanswerable::Expired)
+        );
+
+        // until they are forgotten one more ttl later
+        auth.collect_garbage(1000 + 2 * TTL + 1);
+        assert!(auth.spent.lock().unwrap().contains_key("unanswered"));
+        auth.collect_garbage(1000 + 2 * TTL + 2);
+        assert!(auth.spent.lock().unwrap().is_empty());
+        assert_eq!(
+            consume(&auth, "unanswered", 1000 + 2 * TTL + 2),
+            Err(Unanswerable::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_huge_challenge_ttl() {
+        // a ttl too large to add to now never expires rather than overflowing
+        let auth = verifier().with_challenge_ttl(u64::MAX);
+        pending_at(&auth, "forever", 1000);
+        pending_at(&auth, "swept", 1000);
+
+        auth.collect_garbage(u64::MAX);
+        assert_eq!(auth.pending.lock().unwrap().len(), 2);
+        assert!(consume(&auth, "forever", u64::MAX).is_ok());
+        assert_eq!(
+            consume(&auth, "forever", u64::MAX),
+            Err(Unanswerable::Answered)
+        );
+        auth.collect_garbage(u64::MAX);
+        assert!(auth.spent.lock().unwrap().contains_key("forever"));
+    }
+
+    #[tokio::test]
+    async fn test_challenge_is_answered_once() {
+        let auth = verifier();
+        let x = register(&auth, "alice");
+
+        // a wrong s spends the challenge, the right one cannot follow it
+        let (auth_id, s, wrong) = challenge(&auth, "alice", &x).await;
+        assert_eq!(
+            answer(&auth, &auth_id, &wrong).await,
+            Err(Code::Unauthenticated)
+        );
+        assert_eq!(
+            answer(&auth, &auth_id, &s).await,
+            Err(Code::FailedPrecondition)
+        );
+
+        // and a successful answer cannot be replayed
+        let (auth_id, s, _) = challenge(&auth, "alice", &x).await;
+        assert!(answer(&auth, &auth_id, &s).await.is_ok());
+        assert_eq!(
+            answer(&auth, &auth_id, &s).await,
+            Err(Code::FailedPrecondition)
+        );
+        assert_eq!(count(&auth.metrics.replayed_attempts), 2);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_challenges_of_one_user() {
+        let auth = verifier();
+        let x = register(&auth, "alice");
+
+        // two devices ask before either answers, and answer in the other order
+        let (first, first_s, _) = challenge(&auth, "alice", &x).await;
+        let (second, second_s, _) = challenge(&auth, "alice", &x).await;
+        assert_ne!(first, second);
+
+        let second_session = answer(&auth, &second, &second_s).await.unwrap();
+        let first_session = answer(&auth, &first, &first_s).await.unwrap();
+        assert_ne!(first_session, second_session);
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let auth = verifier();
+        let x = register(&auth, "alice");
+        let session_id = login(&auth, "alice", &x).await;
+        assert_eq!(validate(&auth, &session_id).await, Ok("alice".to_string()));
+
+        // a refresh keeps the id and pushes the expiry one ttl past now
+        let before = unix_time();
+        let expires_at = refresh(&auth, &session_id).await.unwrap();
+        assert!(expires_at >= before + auth.session_ttl);
+        assert_eq!(validate(&auth, &session_id).await, Ok("alice".to_string()));
+
+        // after logout nothing takes it, and logging out again is fine
+        logout(&auth, &session_id).await;
+        assert_eq!(
+            validate(&auth, &session_id).await,
+            Err(Code::Unauthenticated)
+        );
+        assert_eq!(
+            refresh(&auth, &session_id).await,
+            Err(Code::Unauthenticated)
+        );
+        logout(&auth, &session_id).await;
+    }
+
+    #[tokio::test]
+    async fn test_session_expires() {
+        // valid while now < expires_at, so a ttl of 0 ends it as it is issued
+        let auth = verifier().with_session_ttl(0);
+        let x = register(&auth, "alice");
+        let session_id = login(&auth, "alice", &x).await;
+
+        assert_eq!(
+            validate(&auth, &session_id).await,
+            Err(Code::Unauthenticated)
+        );
+        assert_eq!(
+            refresh(&auth, &session_id).await,
+            Err(Code::Unauthenticated)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_huge_session_ttl() {
+        // a ttl too large to add to now lasts until the end of time rather than overflowing
+        let auth = verifier().with_session_ttl(u64::MAX);
+        let x = register(&auth, "alice");
+        let session_id = login(&auth, "alice", &x).await;
+
+        assert_eq!(validate(&auth, &session_id).await, Ok("alice".to_string()));
+        assert_eq!(refresh(&auth, &session_id).await, Ok(u64::MAX));
+    }
+
+    #[tokio::test]
+    async fn test_sessions_end_with_credentials() {
+        let auth = verifier();
+        let x = register(&auth, "alice");
+        let first = login(&auth, "alice", &x).await;
+        let second = login(&auth, "alice", &x).await;
+        let bob = register(&auth, "bob");
+        let bob_session = login(&auth, "bob", &bob).await;
+
+        // registering again ends every session of the user, and only theirs
+        let group = auth.proof.group();
+        let x = SecretScalar::new(auth.proof.generate_random_number());
+        let [y1, y2] = auth.proof.create_pair(&x).unwrap();
+        let request = RegisterRequest {
+            identifier: "alice".to_string(),
+            y1: group.encode_element(&y1),
+            y2: group.encode_element(&y2),
+            kdf: Some(Kdf::default().to_proto(&[0; 16])),
+            protocol: auth::Protocol::ChaumPedersen as i32,
+        };
+        auth.register(Request::new(request)).await.unwrap();
+        for session_id in [&first, &second] {
+            assert_eq!(
+                validate(&auth, session_id).await,
+                Err(Code::Unauthenticated)
+            );
+        }
+        assert_eq!(validate(&auth, &bob_session).await, Ok("bob".to_string()));
+
+        // so does answering two challenges with one commitment
+        let session_id = login(&auth, "alice", &x).await;
+        // record_transcript takes answers as verified, the s does not matter here
+        let [r1, r2] = auth.proof.create_pair(&x).unwrap();
+        for flags in [false, true] {
+            let c = auth.generate_challenge();
+            let flagged = auth.record_transcript("alice", &r1, Some(&r2), &c, &c);
+            assert_eq!(flagged.unwrap(), Some(flags));
+        }
+        assert!(auth.store.get("alice").unwrap().unwrap().compromised);
+        assert_eq!(
+            validate(&auth, &session_id).await,
+            Err(Code::Unauthenticated)
+        );
+        assert_eq!(
+            refresh(&auth, &session_id).await,
+            Err(Code::Unauthenticated)
+        );
+        assert_eq!(validate(&auth, &bob_session).await, Ok("bob".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_commitment_gets_one_challenge() {
+        let auth = verifier();
+        let x = register(&auth, "alice");
+
+        // a commitment waiting for its answer gets no second challenge
+        let [r1, r2] = random_commitment(&auth);
+        assert!(ask(&auth, "alice", &r1, &r2).await.is_ok());
+        assert_eq!(
+            ask(&auth, "alice", &r1, &r2).await,
+            Err(Code::InvalidArgument)
+        );
+
+        // nor does an answered one, however many unanswered commitments follow it
+        let commitment = auth.proof.prover(&x).commit().unwrap();
+        let (r1, r2) = (*commitment.r1(), *commitment.r2());
+        let response = ask(&auth, "alice", &r1, &r2).await.unwrap();
+        let c = auth.proof.group().decode_scalar(&response.c).unwrap();
+        let s = commitment.respond(&c).unwrap();
+        let s = auth.proof.group().encode_scalar(&s);
+        assert!(answer(&auth, &response.auth_id, &s).await.is_ok());
+        for _ in 0..MAX_COMMITMENTS + 1 {
+            let [r1, r2] = random_commitment(&auth);
+            ask(&auth, "alice", &r1, &r2).await.unwrap();
+        }
+        auth.collect_garbage(unix_time() + 2 * TTL + 1);
+        assert!(auth.recent_commitments.lock().unwrap().is_empty());
+        assert_eq!(
+            ask(&auth, "alice", &r1, &r2).await,
+            Err(Code::InvalidArgument)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_seeded_verifier_is_reproducible() {
+        let seeded = || {
+            let (alpha, beta) = Ristretto255::get_constants();
+            let proof = Proof::with_group(Ristretto255, alpha, beta);
+            AuthImpl::with_rng(proof, Box::new(StdRng::seed_from_u64(7)))
+        };
+        let (first, second) = (seeded(), seeded());
+        let x = register(&first, "alice");
+        second
+            .store
+            .put(first.store.get("alice").unwrap().unwrap())
+            .unwrap();
+
+        let [r1, r2] = random_commitment(&first);
+        assert_eq!(
+            ask(&first, "alice", &r1, &r2).await,
+            ask(&second, "alice", &r1, &r2).await
+        );
+        assert_eq!(
+            login(&first, "alice", &x).await,
+            login(&second, "alice", &x).await
+        );
+    }
+}
@@ -0,0 +1,157 @@
+#![cfg(feature = "serde")]
+
+use cp_protocol::archive::{self, Archive, ArchiveError, Bytes, Parameters, PublicKey, Transcript};
+mod common;
+
+use common::{modp_proof, ristretto_proof};
+use cp_protocol::{Equation, Error, Group, MinimumStrength, Proof, SecretScalar};
+
+fn transcripts<G: Group>(proof: &Proof<G>, count: usize) -> Vec<Transcript> {
+    common::transcripts(proof, count)
+        .iter()
+        .map(|transcript| Transcript::encode(proof, transcript))
+        .collect()
+}
+
+// both encodings give back the same archive, which still verifies under its own parameters
+fn round_trip(archive: &Archive) -> Vec<Archive> {
+    let json = archive::to_json(archive).unwrap();
+    let cbor = archive::to_cbor(archive).unwrap();
+    vec![
+        archive::from_json(&json).unwrap(),
+        archive::from_cbor(&cbor).unwrap(),
+    ]
+}
+
+#[test]
+fn test_archive_round_trip_and_reverify() {
+    let proof = modp_proof();
+    let archive = Archive {
+        parameters: Parameters::modp(&proof),
+        transcripts: transcripts(&proof, 3),
+    };
+    for read in round_trip(&archive) {
+        assert_eq!(read, archive);
+        let proof = read
+            .parameters
+            .modp_proof(&MinimumStrength::default())
+            .unwrap();
+        assert_eq!(proof.verify_archived(&read.transcripts), Ok(()));
+    }
+
+    let proof = ristretto_proof();
+    let archive = Archive {
+        parameters: Parameters::ristretto255(&proof),
+        transcripts: transcripts(&proof, 3),
+    };
+    for read in round_trip(&archive) {
+        assert_eq!(read, archive);
+        let proof = read.parameters.ristretto255_proof().unwrap();
+        assert_eq!(proof.verify_archived(&read.transcripts), Ok(()));
+        assert_eq!(
+            read.parameters.modp_proof(&MinimumStrength::none()).err(),
+            Some(Error::Encoding("parameters"))
+        );
+    }
+}
+
+#[test]
+fn test_tampered_archive() {
+    let proof = ristretto_proof();
+    let mut transcripts = transcripts(&proof, 4);
+
+    // a swapped challenge and response, and an s that is not a canonical scalar
+    let response = transcripts[1].response.s.clone();
+    transcripts[1].response.s = transcripts[1].challenge.c.clone();
+    transcripts[1].challenge.c = response;
+    transcripts[3].response.s = Bytes(vec![0xff; 32]);
+
+    assert_eq!(
+        proof.verify_archived(&transcripts),
+        Err(vec![
+            (1, Error::VerificationFailed(Equation::R1)),
+            (3, Error::Encoding("s")),
+        ])
+    );
+
+    // generators outside the group or a truncated modulus never become a Proof
+    let parameters = Parameters::Ristretto255 {
+        alpha: Bytes(vec![0; 32]),
+        beta: Bytes(vec![0xff; 32]),
+    };
+    assert_eq!(
+        parameters.ristretto255_proof().err(),
+        Some(Error::InvalidElement("alpha"))
+    );
+
+    let Parameters::Modp {
+        p: p_bytes,
+        q: q_bytes,
+        alpha: alpha_bytes,
+        beta: beta_bytes,
+    } = Parameters::modp(&modp_proof())
+    else {
+        unreachable!()
+    };
+    let parameters = Parameters::Modp {
+        p: Bytes([&[0], &p_bytes.0[..]].concat()),
+        q: q_bytes,
+        alpha: alpha_bytes,
+        beta: beta_bytes,
+    };
+    assert_eq!(
+        parameters.modp_proof(&MinimumStrength::default()).err(),
+        Some(Error::Encoding("p"))
+    );
+}
+
+#[test]
+fn test_envelope() {
+    let proof = ristretto_proof();
+    let x = SecretScalar::new(proof.generate_random_number());
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    let public_key = PublicKey::encode(&proof, &y1, &y2);
+
+    let json = archive::to_json(&public_key).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["version"], archive::FORMAT_VERSION);
+    assert_eq!(value["kind"], "public_key");
+    assert_eq!(
+        value["body"]["y1"],
+        hex::encode(proof.group().encode_element(&y1))
+    );
+    assert_eq!(
+        archive::from_json::<PublicKey>(&json)
+            .unwrap()
+            .decode(&proof),
+        Ok([y1, y2])
+    );
+
+    // a public key is not a transcript, and a future version is refused before its body
+    // is looked at
+    assert!(matches!(
+        archive::from_json::<Transcript>(&json),
+        Err(ArchiveError::UnexpectedKind {
+            expected: "transcript",
+            ..
+        })
+    ));
+    let future = json.replace("\"version\": 1", "\"version\": 2");
+    assert!(matches!(
+        archive::from_json::<PublicKey>(&future),
+        Err(ArchiveError::UnsupportedVersion(2))
+    ));
+
+    let cbor = archive::to_cbor(&public_key).unwrap();
+    assert!(matches!(
+        archive::from_cbor::<Parameters>(&cbor),
+        Err(ArchiveError::UnexpectedKind {
+            expected: "parameters",
+            ..
+        })
+    ));
+    assert!(matches!(
+        archive::from_cbor::<PublicKey>(&cbor[..cbor.len() - 1]),
+        Err(ArchiveError::Cbor(_))
+    ));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{modp_proof, ristretto_proof, transcripts};
+use cp_protocol::{Equation, Error, Group, ModpGroup, SecretScalar};
+use num_bigint::BigUint;
+
+#[test]
+fn test_batch_accepts_valid_transcripts() {
+    let proof = modp_proof();
+    assert!(proof.verify_batch(&transcripts(&proof, 10)).is_ok());
+    assert!(proof.verify_batch(&[]).is_ok());
+
+    let proof = ristretto_proof();
+    assert!(proof.verify_batch(&transcripts(&proof, 10)).is_ok());
+}
+
+// big enough to be combined rather than checked one by one
+#[test]
+fn test_batch_identifies_bad_transcripts() {
+    let proof = modp_proof();
+    let group = proof.group();
+    let mut batch = transcripts(&proof, ModpGroup::BATCH_THRESHOLD);
+
+    batch[3].s = group.scalar_add(&batch[3].s, &BigUint::from(1u32));
+    batch[7].r2 = batch[6].r2.clone();
+    batch[8].y1 = BigUint::from(0u32);
+
+    assert_eq!(
+        proof.verify_batch(&batch),
+        Err(vec![
+            (3, Error::VerificationFailed(Equation::R1)),
+            (7, Error::VerificationFailed(Equation::R2)),
+            (8, Error::InvalidElement("y1")),
+        ])
+    );
+}
+
+#[test]
+fn test_batch_of_login_proofs() {
+    let proof = ristretto_proof();
+
+    let mut batch = (0..5)
+        .map(|i| {
+            let x = SecretScalar::new(proof.generate_random_number());
+            let [y1, y2] = proof.create_pair(&x).unwrap();
+            let login = proof
+                .prove_non_interactive(&x, "alice", &[i as u8])
+                .unwrap();
+            proof.login_transcript(&y1, &y2, "alice", &[i as u8], &login)
+        })
+        .collect::<Vec<_>>();
+    assert!(proof.verify_batch(&batch).is_ok());
+
+    // a challenge taken from another login does not fit this transcript
+    batch[2].c = batch[1].c;
+    assert_eq!(
+        proof.verify_batch(&batch),
+        Err(vec![(2, Error::VerificationFailed(Equation::R1))])
+    );
+}
+
+#[test]
+fn test_batch_of_registered_keys() {
+    let proof = modp_proof();
+    let group = proof.group();
+    let mut batch = transcripts(&proof, ModpGroup::BATCH_THRESHOLD);
+    assert!(proof.verify_batch_registered(&batch).is_ok());
+
+    // r1 and r2 are still checked, y1 and y2 are taken as registered
+    batch[1].r1 = BigUint::from(0u32);
+    batch[4].s = group.scalar_add(&batch[4].s, &BigUint::from(1u32));
+    assert_eq!(
+        proof.verify_batch_registered(&batch),
+        Err(vec![
+            (1, Error::InvalidElement("r1")),
+            (4, Error::VerificationFailed(Equation::R1)),
+        ])
+    );
+
+    // a single transcript is checked on its own, with the same errors as verify
+    for t in &batch[..2] {
+        let single = std::slice::from_ref(t);
+        let expected = proof.verify(&t.r1, &t.r2, &t.y1, &t.y2, &t.c, &t.s);
+        assert_eq!(
+            proof.verify_batch_registered(single),
+            expected.clone().map_err(|error| vec![(0, error)])
+        );
+        assert_eq!(
+            proof.verify_batch(single),
+            expected.map_err(|error| vec![(0, error)])
+        );
+    }
+}
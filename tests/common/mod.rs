@@ -0,0 +1,38 @@
+// fixtures shared by the integration tests and benches/batch.rs, each of which only uses
+// some of them
+#![allow(dead_code)]
+
+use cp_protocol::{Group, Proof, Ristretto255, SecretScalar, Transcript};
+
+pub fn modp_proof() -> Proof {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    Proof::new(p, q, alpha, beta)
+}
+
+pub fn ristretto_proof() -> Proof<Ristretto255> {
+    let (alpha, beta) = Ristretto255::get_constants();
+    Proof::with_group(Ristretto255, alpha, beta)
+}
+
+// valid transcripts, each under a fresh key
+pub fn transcripts<G: Group>(proof: &Proof<G>, count: usize) -> Vec<Transcript<G>> {
+    (0..count)
+        .map(|_| {
+            let x = SecretScalar::new(proof.generate_random_number());
+            let [y1, y2] = proof.create_pair(&x).unwrap();
+            let commitment = proof.prover(&x).commit().unwrap();
+            let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+            let c = proof.generate_random_number();
+            let s = commitment.respond(&c).unwrap();
+
+            Transcript {
+                r1,
+                r2,
+                y1,
+                y2,
+                c,
+                s,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,64 @@
+use cp_protocol::encoding::{decode_fixed, encode_fixed, fixed_length};
+use cp_protocol::parameter_sets::PARAMETER_SETS;
+use cp_protocol::{Error, Group, Proof, SecretScalar};
+use num_bigint::BigUint;
+
+#[test]
+fn test_fixed_encoding() {
+    let bound = BigUint::from(0x1_0001u32);
+    assert_eq!(fixed_length(&bound), 3);
+
+    assert_eq!(encode_fixed(&BigUint::from(0u32), 3), [0, 0, 0]);
+    assert_eq!(encode_fixed(&BigUint::from(0x102u32), 3), [0, 1, 2]);
+    assert_eq!(
+        decode_fixed(&[0, 1, 2], &bound),
+        Some(BigUint::from(0x102u32))
+    );
+    assert_eq!(
+        decode_fixed(&[1, 0, 0], &bound),
+        Some(BigUint::from(0x1_0000u32))
+    );
+
+    // too short, too long, equal to and above the bound
+    assert_eq!(decode_fixed(&[1, 2], &bound), None);
+    assert_eq!(decode_fixed(&[0, 0, 1, 2], &bound), None);
+    assert_eq!(decode_fixed(&[1, 0, 1], &bound), None);
+    assert_eq!(decode_fixed(&[0xff, 0xff, 0xff], &bound), None);
+    assert_eq!(decode_fixed(&[], &bound), None);
+}
+
+#[test]
+fn test_modp_encodings_are_fixed_length_and_strict() {
+    for set in PARAMETER_SETS {
+        let (p, q, alpha, beta) = set.constants().unwrap();
+        let proof = Proof::new(p.clone(), q.clone(), alpha, beta);
+        let group = proof.group();
+
+        let x = SecretScalar::new(proof.generate_random_number());
+        let [y1, _] = proof.create_pair(&x).unwrap();
+        let bytes = group.encode_element(&y1);
+        assert_eq!(bytes.len(), group.element_length(), "{}", set.name);
+        assert_eq!(proof.decode_element("y1", &bytes), Ok(y1.clone()));
+
+        // one byte too many or too few
+        for bytes in [[&[0], &bytes[..]].concat(), bytes[1..].to_vec()] {
+            let result = proof.decode_element("y1", &bytes);
+            assert_eq!(result, Err(Error::Encoding("y1")), "{}", set.name);
+        }
+        // p and above are unreduced forms of smaller values
+        let result = proof.decode_element("y1", &encode_fixed(&p, group.element_length()));
+        assert_eq!(result, Err(Error::Encoding("y1")), "{}", set.name);
+        // canonical but outside the subgroup
+        let result = proof.decode_element("y1", &group.encode_element(&(&p - 1u32)));
+        assert_eq!(result, Err(Error::InvalidElement("y1")), "{}", set.name);
+
+        // short scalars are padded, not shortened
+        let bytes = group.encode_scalar(&BigUint::from(1u32));
+        assert_eq!(bytes.len(), group.scalar_length(), "{}", set.name);
+        assert_eq!(proof.decode_scalar("s", &bytes), Ok(BigUint::from(1u32)));
+        assert_eq!(proof.decode_scalar("s", &[1]), Err(Error::Encoding("s")));
+
+        let result = proof.decode_scalar("s", &encode_fixed(&q, group.scalar_length()));
+        assert_eq!(result, Err(Error::Encoding("s")), "{}", set.name);
+    }
+}
@@ -0,0 +1,209 @@
+use cp_protocol::{Equation, Error, Group, ModpGroup, ParameterSet, Proof, SecretScalar};
+use num_bigint::{BigUint, RandBigInt};
+use rand_core::{CryptoRngCore, RngCore};
+
+// additive group of integers mod a small prime, exp is a multiplication
+// the discrete log is trivial here but the protocol logic does not care
+#[derive(Debug)]
+struct AdditiveGroup {
+    q: u64,
+}
+
+impl Group for AdditiveGroup {
+    type Element = u64;
+    type Scalar = u64;
+    type FixedBase = u64;
+
+    fn parameters(&self) -> Vec<u8> {
+        self.q.to_be_bytes().to_vec()
+    }
+
+    fn identity(&self) -> u64 {
+        0
+    }
+
+    fn exp(&self, base: &u64, exp: &u64) -> u64 {
+        base * exp % self.q
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        (a + b) % self.q
+    }
+
+    fn exp_secret(&self, base: &u64, exp: &u64) -> u64 {
+        self.exp(base, exp)
+    }
+
+    fn exp_fixed_base_secret(&self, table: &u64, exp: &u64) -> u64 {
+        self.exp(table, exp)
+    }
+
+    fn fixed_base(&self, base: &u64) -> u64 {
+        *base
+    }
+
+    fn exp_fixed_base(&self, table: &u64, exp: &u64) -> u64 {
+        self.exp(table, exp)
+    }
+
+    fn is_member(&self, element: &u64) -> bool {
+        *element < self.q
+    }
+
+    fn random_scalar(&self, rng: &mut dyn CryptoRngCore) -> u64 {
+        rng.next_u64() % self.q
+    }
+
+    fn is_zero(&self, scalar: &u64) -> bool {
+        *scalar == 0
+    }
+
+    fn is_valid_scalar(&self, scalar: &u64) -> bool {
+        *scalar < self.q
+    }
+
+    fn scalar_length(&self) -> usize {
+        8
+    }
+
+    fn scalar_add(&self, a: &u64, b: &u64) -> u64 {
+        (a + b) % self.q
+    }
+
+    fn scalar_sub(&self, a: &u64, b: &u64) -> u64 {
+        (a + self.q - b % self.q) % self.q
+    }
+
+    fn scalar_mul(&self, a: &u64, b: &u64) -> u64 {
+        a * b % self.q
+    }
+
+    // brute force is fine for a toy q
+    fn scalar_invert(&self, a: &u64) -> Option<u64> {
+        (1..self.q).find(|b| a * b % self.q == 1)
+    }
+
+    fn scalar_sub_mul(&self, k: &u64, c: &u64, x: &u64) -> u64 {
+        self.scalar_sub(k, &self.scalar_mul(c, x))
+    }
+
+    fn scalar_from_u128(&self, value: u128) -> u64 {
+        (value % self.q as u128) as u64
+    }
+
+    fn zeroize_scalar(scalar: &mut u64) {
+        *scalar = 0;
+    }
+
+    fn scalar_from_bytes(&self, bytes: &[u8]) -> u64 {
+        bytes
+            .iter()
+            .fold(0, |acc, b| (acc * 256 + *b as u64) % self.q)
+    }
+
+    fn element_length(&self) -> usize {
+        8
+    }
+
+    fn encode_element(&self, element: &u64) -> Vec<u8> {
+        element.to_be_bytes().to_vec()
+    }
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<u64> {
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+
+    fn encode_scalar(&self, scalar: &u64) -> Vec<u8> {
+        scalar.to_be_bytes().to_vec()
+    }
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<u64> {
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+#[test]
+fn test_mock_group() {
+    let proof = Proof::with_group(AdditiveGroup { q: 101 }, 3, 7);
+
+    let x = SecretScalar::new(42);
+    let c = 77;
+
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    assert_eq!(y1, 3 * 42 % 101);
+    assert_eq!(y2, 7 * 42 % 101);
+
+    let commitment = proof.prover(&x).commit().unwrap();
+    let (r1, r2) = (*commitment.r1(), *commitment.r2());
+    let s = commitment.respond(&c).unwrap();
+
+    assert!(proof.verify(&r1, &r2, &y1, &y2, &c, &s).is_ok());
+    assert_eq!(
+        proof.verify(&r1, &r2, &y1, &y2, &c, &((s + 1) % 101)),
+        Err(Error::VerificationFailed(Equation::R1))
+    );
+}
+
+#[test]
+fn test_modp_group() {
+    let group = ModpGroup::new(BigUint::from(23u32), BigUint::from(11u32));
+
+    assert!(group.is_member(&BigUint::from(4u32)));
+    assert!(group.is_member(&BigUint::from(1u32)));
+    // 5 generates the whole group of order 22
+    assert!(!group.is_member(&BigUint::from(5u32)));
+    assert!(!group.is_member(&BigUint::from(0u32)));
+    assert!(!group.is_member(&BigUint::from(23u32)));
+
+    assert_eq!(
+        group.scalar_sub(&BigUint::from(3u32), &BigUint::from(5u32)),
+        BigUint::from(9u32)
+    );
+    assert_eq!(
+        group.scalar_sub(&BigUint::from(5u32), &BigUint::from(5u32)),
+        BigUint::from(0u32)
+    );
+    assert_eq!(
+        group.mul(&BigUint::from(4u32), &BigUint::from(9u32)),
+        BigUint::from(13u32)
+    );
+}
+
+// the constant time backend must agree with plain num-bigint arithmetic
+#[test]
+fn test_modp_secret_arithmetic() {
+    let mut rng = rand::thread_rng();
+    let toy = ModpGroup::new(BigUint::from(23u32), BigUint::from(11u32));
+    let groups = ParameterSet::names()
+        .map(|name| ParameterSet::by_name(name).unwrap().group().unwrap())
+        .chain([toy]);
+
+    for group in groups {
+        let (p, q) = (group.p(), group.q());
+        let base = group.exp(&BigUint::from(4u32), &((p - 1u32) / q));
+
+        for _ in 0..3 {
+            let [k, c, x] = [0; 3].map(|_| rng.gen_biguint_below(q));
+            assert_eq!(group.exp_secret(&base, &x), group.exp(&base, &x));
+            assert_eq!(
+                group.scalar_sub_mul(&k, &c, &x),
+                group.scalar_sub(&k, &group.scalar_mul(&c, &x))
+            );
+
+            let mut bytes = vec![0u8; group.scalar_length() + 16];
+            rng.fill_bytes(&mut bytes);
+            assert_eq!(
+                group.scalar_from_bytes(&bytes),
+                BigUint::from_bytes_be(&bytes) % q
+            );
+        }
+
+        let table = group.fixed_base(&base);
+        let q_minus_one = q - 1u32;
+        assert_eq!(
+            group.exp_fixed_base_secret(&table, &q_minus_one),
+            group.exp(&base, &q_minus_one)
+        );
+        assert_eq!(group.exp_secret(&base, &BigUint::ZERO), group.identity());
+    }
+}
@@ -0,0 +1,133 @@
+use cp_protocol::auth::KdfParameters;
+use cp_protocol::kdf::{SALT_LENGTH, derive_secret, generate_salt};
+use cp_protocol::{Group, Kdf, KdfError, Proof, Ristretto255, Secret};
+
+// cheap cost parameters so the tests stay fast
+const KDFS: [Kdf; 3] = [
+    Kdf::Argon2id {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    },
+    Kdf::Scrypt {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    },
+    Kdf::Pbkdf2Sha512 { iterations: 10 },
+];
+
+#[test]
+fn test_derive_secret_is_deterministic() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha, beta);
+    let salt = generate_salt();
+    assert_eq!(salt.len(), SALT_LENGTH);
+
+    for kdf in KDFS {
+        let x1 = derive_secret(proof.group(), &kdf, b"password", &salt).unwrap();
+        let x2 = derive_secret(proof.group(), &kdf, b"password", &salt).unwrap();
+        assert_eq!(x1.expose_secret(), x2.expose_secret());
+        assert!(!proof.group().is_zero(x1.expose_secret()));
+        assert!(x1.expose_secret() < proof.group().q());
+    }
+}
+
+#[test]
+fn test_derive_secret_depends_on_salt_and_password() {
+    let group = Ristretto255;
+    let salt = generate_salt();
+
+    for kdf in KDFS {
+        let x = derive_secret(&group, &kdf, b"password", &salt).unwrap();
+        let other_salt = derive_secret(&group, &kdf, b"password", &generate_salt()).unwrap();
+        let other_password = derive_secret(&group, &kdf, b"passw0rd", &salt).unwrap();
+
+        assert_ne!(x.expose_secret(), other_salt.expose_secret());
+        assert_ne!(x.expose_secret(), other_password.expose_secret());
+    }
+}
+
+#[test]
+fn test_derived_secret_authenticates() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+    let salt = generate_salt();
+
+    let x = derive_secret(proof.group(), &KDFS[0], b"password", &salt).unwrap();
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+
+    let login = derive_secret(proof.group(), &KDFS[0], b"password", &salt).unwrap();
+    let commitment = proof.prover(&login).commit().unwrap();
+    let (r1, r2) = (*commitment.r1(), *commitment.r2());
+    let c = proof.generate_random_number();
+    let s = commitment.respond(&c).unwrap();
+    assert!(proof.verify(&r1, &r2, &y1, &y2, &c, &s).is_ok());
+}
+
+#[test]
+fn test_invalid_kdf_parameters() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha, beta);
+    let salt = generate_salt();
+    let invalid = [
+        Kdf::Argon2id {
+            memory_kib: 1,
+            iterations: 1,
+            parallelism: 1,
+        },
+        Kdf::Scrypt {
+            log_n: 4,
+            r: 0,
+            p: 1,
+        },
+        Kdf::Pbkdf2Sha512 { iterations: 0 },
+    ];
+
+    for kdf in invalid {
+        let result = derive_secret(proof.group(), &kdf, b"password", &salt);
+        assert!(matches!(result, Err(KdfError::InvalidParameters(_))));
+    }
+}
+
+#[test]
+fn test_kdf_from_str() {
+    assert_eq!("argon2id".parse::<Kdf>(), Ok(Kdf::default()));
+    assert_eq!("scrypt".parse::<Kdf>().unwrap().name(), "scrypt");
+    assert_eq!("pbkdf2".parse::<Kdf>().unwrap().name(), "pbkdf2");
+    assert!("md5".parse::<Kdf>().is_err());
+}
+
+#[test]
+fn test_kdf_proto_round_trip() {
+    let salt = generate_salt();
+    for kdf in KDFS {
+        assert_eq!(
+            Kdf::from_proto(kdf.to_proto(&salt)),
+            Ok((kdf, salt.clone()))
+        );
+    }
+
+    // whatever the other side sends is an error, never a panic
+    let scrypt = KDFS[1].to_proto(&salt);
+    let invalid = [
+        KdfParameters {
+            log_n: 256,
+            ..scrypt.clone()
+        },
+        KdfParameters {
+            algorithm: "md5".to_string(),
+            ..scrypt.clone()
+        },
+        KdfParameters {
+            salt: vec![1; 4],
+            ..scrypt
+        },
+    ];
+    for parameters in invalid {
+        assert!(matches!(
+            Kdf::from_proto(parameters),
+            Err(KdfError::InvalidParameters(_))
+        ));
+    }
+}
@@ -1,5 +1,7 @@
-use cp_protocol::Proof;
-use hex;
+#[cfg(feature = "hazmat")]
+use cp_protocol::hazmat;
+use cp_protocol::{Equation, Error, Proof, Secret, SecretScalar};
+use cp_protocol::{Group, Ristretto255};
 use num_bigint::BigUint;
 
 #[test]
@@ -9,33 +11,55 @@ fn test() {
     let p = BigUint::from(23u32);
     let q = BigUint::from(11u32);
 
-    let x = BigUint::from(6u32);
-    let k = BigUint::from(7u32);
+    let x = SecretScalar::new(BigUint::from(6u32));
     let c = BigUint::from(4u32);
 
     let proof = Proof::new(p, q, alpha, beta);
 
-    let [y1, y2] = proof.create_pair(&x);
+    let [y1, y2] = proof.create_pair(&x).unwrap();
 
     assert_eq!(y1, BigUint::from(2u32));
     assert_eq!(y2, BigUint::from(3u32));
 
-    let [r1, r2] = proof.create_pair(&k);
+    // r1, r2 and s as k = 7 gives them, see test_fixed_nonce
+    let (r1, r2) = (BigUint::from(8u32), BigUint::from(4u32));
+    let s = BigUint::from(5u32);
 
-    assert_eq!(r1, BigUint::from(8u32));
-    assert_eq!(r2, BigUint::from(4u32));
+    let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
+    assert!(result.is_ok());
 
-    let s = proof.solve(&k, &c, &x);
-    assert_eq!(s, BigUint::from(5u32));
+    // s = k - c * x with x = 7
+    let s = BigUint::from(1u32);
 
     let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
-    assert!(result);
+    assert_eq!(result, Err(Error::VerificationFailed(Equation::R1)));
+}
+
+// the known answers of test, from a fixed k that only the hazmat feature hands out
+#[cfg(feature = "hazmat")]
+#[test]
+fn test_fixed_nonce() {
+    let proof = Proof::new(
+        BigUint::from(23u32),
+        BigUint::from(11u32),
+        BigUint::from(4u32),
+        BigUint::from(9u32),
+    );
+    let c = BigUint::from(4u32);
 
-    let x_fake = BigUint::from(7u32);
-    let s = proof.solve(&k, &c, &x_fake);
+    let k = hazmat::nonce(BigUint::from(7u32));
+    let [r1, r2] = proof.create_pair(&k).unwrap();
+    assert_eq!(r1, BigUint::from(8u32));
+    assert_eq!(r2, BigUint::from(4u32));
 
-    let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
-    assert!(!result);
+    let x = SecretScalar::new(BigUint::from(6u32));
+    let s = hazmat::solve(&proof, k, &c, &x).unwrap();
+    assert_eq!(s, BigUint::from(5u32));
+
+    let x_fake = SecretScalar::new(BigUint::from(7u32));
+    let k = hazmat::nonce(BigUint::from(7u32));
+    let s = hazmat::solve(&proof, k, &c, &x_fake).unwrap();
+    assert_eq!(s, BigUint::from(1u32));
 }
 
 #[test]
@@ -44,23 +68,23 @@ fn test_rand() {
     let beta = BigUint::from(9u32);
     let p = BigUint::from(23u32);
     let q = BigUint::from(11u32);
-    let x = BigUint::from(6u32);
+    let x = SecretScalar::new(BigUint::from(6u32));
     let proof = Proof::new(p, q, alpha, beta);
 
-    let k = proof.generate_random_number();
     let c = proof.generate_random_number();
 
-    let [y1, y2] = proof.create_pair(&x);
+    let [y1, y2] = proof.create_pair(&x).unwrap();
 
     assert_eq!(y1, BigUint::from(2u32));
     assert_eq!(y2, BigUint::from(3u32));
 
-    let [r1, r2] = proof.create_pair(&k);
+    let commitment = proof.prover(&x).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
 
-    let s = proof.solve(&k, &c, &x);
+    let s = commitment.respond(&c).unwrap();
 
     let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
-    assert!(result);
+    assert!(result.is_ok());
 }
 
 #[test]
@@ -82,24 +106,26 @@ fn test_1024_bits() {
     // any element of the group elevated to x is also a generator
     let beta = alpha.modpow(&Proof::generate_random_number_below(&q), &p);
 
-    let x = &Proof::generate_random_number_below(&q);
-    let k = &Proof::generate_random_number_below(&q);
+    let x = &SecretScalar::new(Proof::generate_random_number_below(&q));
     let c = &Proof::generate_random_number_below(&q);
 
     let proof = Proof::new(p, q, alpha, beta);
 
-    let [y1, y2] = proof.create_pair(&x);
-    let [r1, r2] = proof.create_pair(&k);
-    let s = proof.solve(&k, &c, &x);
+    let [y1, y2] = proof.create_pair(x).unwrap();
+    let commitment = proof.prover(x).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let s = commitment.respond(c).unwrap();
 
-    let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
-    assert!(result);
+    let result = proof.verify(&r1, &r2, &y1, &y2, c, &s);
+    assert!(result.is_ok());
 
-    let x_fake = BigUint::from(7u32);
-    let s = proof.solve(&k, &c, &x_fake);
+    let x_fake = SecretScalar::new(BigUint::from(7u32));
+    let commitment = proof.prover(&x_fake).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let s = commitment.respond(c).unwrap();
 
-    let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
-    assert!(!result);
+    let result = proof.verify(&r1, &r2, &y1, &y2, c, &s);
+    assert_eq!(result, Err(Error::VerificationFailed(Equation::R1)));
 }
 
 #[test]
@@ -121,24 +147,287 @@ fn test_2048_bits() {
     // any element of the group elevated to x is also a generator
     let beta = alpha.modpow(&Proof::generate_random_number_below(&q), &p);
 
-    let x = &Proof::generate_random_number_below(&q);
-    let k = &Proof::generate_random_number_below(&q);
+    let x = &SecretScalar::new(Proof::generate_random_number_below(&q));
     let c = &Proof::generate_random_number_below(&q);
 
     let proof = Proof::new(p, q, alpha, beta);
 
-    let [y1, y2] = proof.create_pair(&x);
-    let [r1, r2] = proof.create_pair(&k);
+    let [y1, y2] = proof.create_pair(x).unwrap();
+    let commitment = proof.prover(x).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
 
-    let s = proof.solve(&k, &c, &x);
+    let s = commitment.respond(c).unwrap();
 
-    let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
+    let result = proof.verify(&r1, &r2, &y1, &y2, c, &s);
+
+    assert!(result.is_ok());
+
+    let x_fake = SecretScalar::new(BigUint::from(7u32));
+    let commitment = proof.prover(&x_fake).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let s = commitment.respond(c).unwrap();
+
+    let result = proof.verify(&r1, &r2, &y1, &y2, c, &s);
+    assert_eq!(result, Err(Error::VerificationFailed(Equation::R1)));
+}
+
+#[test]
+fn test_ristretto() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+
+    let x = SecretScalar::new(proof.generate_random_number());
+    let c = proof.generate_random_number();
+
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    let commitment = proof.prover(&x).commit().unwrap();
+    let (r1, r2) = (*commitment.r1(), *commitment.r2());
+
+    let s = commitment.respond(&c).unwrap();
 
-    assert!(result);
+    let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
+    assert!(result.is_ok());
 
-    let x_fake = BigUint::from(7u32);
-    let s = proof.solve(&k, &c, &x_fake);
+    let x_fake = SecretScalar::new(Ristretto255.scalar_from_bytes(b"not the password"));
+    let commitment = proof.prover(&x_fake).commit().unwrap();
+    let (r1, r2) = (*commitment.r1(), *commitment.r2());
+    let s = commitment.respond(&c).unwrap();
 
     let result = proof.verify(&r1, &r2, &y1, &y2, &c, &s);
-    assert!(!result);
+    assert_eq!(result, Err(Error::VerificationFailed(Equation::R1)));
+}
+
+#[test]
+fn test_ristretto_encoding() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+
+    let group = proof.group();
+
+    let x = proof.generate_random_number();
+    let [y1, _] = proof.create_pair(&SecretScalar::new(x)).unwrap();
+
+    let bytes = group.encode_element(&y1);
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(group.decode_element(&bytes), Some(y1));
+
+    let bytes = group.encode_scalar(&x);
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(group.decode_scalar(&bytes), Some(x));
+
+    assert_eq!(group.decode_element(&[0xff; 32]), None);
+    assert_eq!(group.decode_scalar(&[0xff; 32]), None);
+}
+
+#[test]
+fn test_non_interactive() {
+    let alpha = BigUint::from(4u32);
+    let beta = BigUint::from(9u32);
+    let p = BigUint::from(23u32);
+    let q = BigUint::from(11u32);
+    let x = SecretScalar::new(BigUint::from(6u32));
+    let proof = Proof::new(p, q, alpha, beta);
+
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    let nonce = 1700000000u64.to_be_bytes();
+
+    let login = proof.prove_non_interactive(&x, "alice", &nonce).unwrap();
+    assert!(
+        proof
+            .verify_non_interactive(&y1, &y2, "alice", &nonce, &login)
+            .is_ok()
+    );
+
+    // the verifier recomputes the exact same challenge from the transcript
+    let c = proof.challenge(&y1, &y2, &login.r1, &login.r2, "alice", &nonce);
+    assert!(
+        proof
+            .verify(&login.r1, &login.r2, &y1, &y2, &c, &login.s)
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_non_interactive_ristretto() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+
+    let x = SecretScalar::new(proof.generate_random_number());
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    let nonce = 1700000000u64.to_be_bytes();
+
+    let login = proof.prove_non_interactive(&x, "alice", &nonce).unwrap();
+    assert!(
+        proof
+            .verify_non_interactive(&y1, &y2, "alice", &nonce, &login)
+            .is_ok()
+    );
+    assert!(
+        proof
+            .verify_non_interactive(&y1, &y2, "bob", &nonce, &login)
+            .is_err()
+    );
+    assert!(
+        proof
+            .verify_non_interactive(&y1, &y2, "alice", b"replayed", &login)
+            .is_err()
+    );
+
+    let x_fake = SecretScalar::new(proof.generate_random_number());
+    let forged = proof
+        .prove_non_interactive(&x_fake, "alice", &nonce)
+        .unwrap();
+    assert!(
+        proof
+            .verify_non_interactive(&y1, &y2, "alice", &nonce, &forged)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_errors() {
+    let alpha = BigUint::from(4u32);
+    let beta = BigUint::from(9u32);
+    let p = BigUint::from(23u32);
+    let q = BigUint::from(11u32);
+    let proof = Proof::new(p, q, alpha, beta);
+
+    let x = SecretScalar::new(BigUint::from(6u32));
+    let c = BigUint::from(4u32);
+
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    let session = proof.prover(&x);
+    let commitment = session.commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let s = commitment.respond(&c).unwrap();
+
+    // r1 is right but r2 is not
+    let result = proof.verify(&r1, &BigUint::from(1u32), &y1, &y2, &c, &s);
+    assert_eq!(result, Err(Error::VerificationFailed(Equation::R2)));
+
+    let result = proof.verify(&r1, &r2, &y1, &y2, &c, &BigUint::from(11u32));
+    assert_eq!(result, Err(Error::ScalarOutOfRange("s")));
+
+    let result = session.commit().unwrap().respond(&BigUint::from(12u32));
+    assert_eq!(result, Err(Error::ScalarOutOfRange("c")));
+
+    let result = proof.create_pair(&SecretScalar::new(BigUint::from(11u32)));
+    assert_eq!(result, Err(Error::ScalarOutOfRange("exponent")));
+
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+    assert_eq!(
+        proof.decode_element("r1", &[0xff; 32]),
+        Err(Error::Encoding("r1"))
+    );
+    assert_eq!(
+        proof.decode_scalar("s", &[0xff; 32]),
+        Err(Error::Encoding("s"))
+    );
+}
+
+#[test]
+fn test_membership() {
+    let alpha = BigUint::from(4u32);
+    let beta = BigUint::from(9u32);
+    let p = BigUint::from(23u32);
+    let q = BigUint::from(11u32);
+    let proof = Proof::new(p, q, alpha, beta);
+
+    let x = SecretScalar::new(BigUint::from(6u32));
+    let c = BigUint::from(4u32);
+
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    let commitment = proof.prover(&x).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let s = commitment.respond(&c).unwrap();
+
+    // 22 = -1 has order 2, 5 generates the whole group, 0 and 23 are out of range
+    for element in [0u32, 5, 22, 23] {
+        let element = BigUint::from(element);
+
+        let result = proof.verify(&element, &r2, &y1, &y2, &c, &s);
+        assert_eq!(result, Err(Error::InvalidElement("r1")));
+
+        let result = proof.verify(&r1, &r2, &y1, &element, &c, &s);
+        assert_eq!(result, Err(Error::InvalidElement("y2")));
+
+        // 23 = p has no canonical encoding at all
+        let result = proof.decode_element("y1", &element.to_bytes_be());
+        let expected = if element < *proof.group().p() {
+            Error::InvalidElement("y1")
+        } else {
+            Error::Encoding("y1")
+        };
+        assert_eq!(result, Err(expected));
+    }
+
+    assert_eq!(proof.decode_element("y1", &y1.to_bytes_be()), Ok(y1));
+    assert_eq!(proof.decode_scalar("s", &[11]), Err(Error::Encoding("s")));
+    assert_eq!(proof.decode_scalar("s", &[10]), Ok(BigUint::from(10u32)));
+}
+
+#[test]
+fn test_prover_session() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+
+    let x = SecretScalar::new(proof.generate_random_number());
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+
+    let session = proof.prover(&x);
+    let first = session.commit().unwrap();
+    let second = session.commit().unwrap();
+
+    // every commitment draws its own k
+    assert_ne!(first.r1(), second.r1());
+
+    for commitment in [first, second] {
+        let (r1, r2) = (*commitment.r1(), *commitment.r2());
+        let c = proof.generate_random_number();
+
+        // respond takes the commitment by value, it cannot be answered twice
+        let s = commitment.respond(&c).unwrap();
+        assert!(proof.verify(&r1, &r2, &y1, &y2, &c, &s).is_ok());
+    }
+}
+
+// a deterministic commitment repeats k when the context repeats
+#[test]
+fn test_nonce_reuse_reveals_secret() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha, beta);
+
+    let x = proof.generate_random_number();
+    let secret = SecretScalar::new(x.clone());
+
+    // the same k answers two different challenges
+    let c1 = proof.generate_random_number();
+    let c2 = proof.generate_random_number();
+    let commit = || proof.prover(&secret).commit_deterministic(b"reused", None);
+    let s1 = commit().unwrap().respond(&c1).unwrap();
+    let s2 = commit().unwrap().respond(&c2).unwrap();
+
+    let extracted = proof.extract_secret(&c1, &s1, &c2, &s2).unwrap();
+    assert_eq!(*extracted.expose_secret(), x);
+
+    assert!(proof.extract_secret(&c1, &s1, &c1, &s1).is_none());
+}
+
+#[test]
+fn test_nonce_reuse_reveals_secret_ristretto() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+
+    let x = proof.generate_random_number();
+    let secret = SecretScalar::new(x);
+
+    let c1 = proof.generate_random_number();
+    let c2 = proof.generate_random_number();
+    let commit = || proof.prover(&secret).commit_deterministic(b"reused", None);
+    let s1 = commit().unwrap().respond(&c1).unwrap();
+    let s2 = commit().unwrap().respond(&c2).unwrap();
+
+    let extracted = proof.extract_secret(&c1, &s1, &c2, &s2).unwrap();
+    assert_eq!(*extracted.expose_secret(), x);
 }
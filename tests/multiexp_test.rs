@@ -0,0 +1,105 @@
+use cp_protocol::{Equation, Error, Group, Proof, Ristretto255, SecretScalar, Transcript};
+use num_bigint::BigUint;
+
+mod common;
+
+use common::{modp_proof, ristretto_proof};
+
+// the fast paths must agree with plain exp and mul on every input
+fn check_against_exp<G: Group>(group: &G, base: &G::Element, exponents: &[G::Scalar]) {
+    let table = group.fixed_base(base);
+    for exp in exponents {
+        assert_eq!(group.exp_fixed_base(&table, exp), group.exp(base, exp));
+    }
+
+    let bases: Vec<_> = exponents.iter().map(|exp| group.exp(base, exp)).collect();
+    let terms: Vec<_> = bases.iter().zip(exponents).collect();
+    let expected = terms.iter().fold(group.identity(), |product, (base, exp)| {
+        group.mul(&product, &group.exp(base, exp))
+    });
+    assert_eq!(group.multi_exp(&terms), expected);
+    assert_eq!(
+        group.multi_exp(&terms[..1]),
+        group.exp(&bases[0], &exponents[0])
+    );
+    assert_eq!(group.multi_exp(&[]), group.identity());
+}
+
+#[test]
+fn test_modp_fixed_base_and_multi_exp() {
+    let proof = modp_proof();
+    let group = proof.group();
+    let q = group.q();
+    let (_, _, alpha, _) = Proof::get_constants().unwrap();
+
+    let mut exponents = vec![
+        BigUint::from(0u32),
+        BigUint::from(1u32),
+        q - 1u32,
+        // above q the table falls back to modpow
+        q + 5u32,
+    ];
+    exponents.extend((0..5).map(|_| proof.generate_random_number()));
+
+    check_against_exp(group, &alpha, &exponents);
+
+    // a toy group whose q has fewer bits than the comb has teeth
+    let group = cp_protocol::ModpGroup::new(BigUint::from(23u32), BigUint::from(11u32));
+    let exponents: Vec<_> = (0..11u32).map(BigUint::from).collect();
+    check_against_exp(&group, &BigUint::from(4u32), &exponents);
+}
+
+#[test]
+fn test_ristretto_fixed_base_and_multi_exp() {
+    let proof = ristretto_proof();
+    let (_, beta) = Ristretto255::get_constants();
+
+    let exponents: Vec<_> = (0..5).map(|_| proof.generate_random_number()).collect();
+    check_against_exp(&Ristretto255, &beta, &exponents);
+}
+
+fn check_tables<G: Group + Clone>(plain: Proof<G>, fast: Proof<G>) {
+    let x = SecretScalar::new(plain.generate_random_number());
+    let [y1, y2] = plain.create_pair(&x).unwrap();
+    assert_eq!(fast.create_pair(&x).unwrap(), [y1.clone(), y2.clone()]);
+
+    let commitment = fast.prover(&x).commit().unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let c = fast.generate_random_number();
+    let s = commitment.respond(&c).unwrap();
+    let transcript = Transcript {
+        r1: r1.clone(),
+        r2: r2.clone(),
+        y1: y1.clone(),
+        y2: y2.clone(),
+        c: c.clone(),
+        s: s.clone(),
+    };
+
+    for proof in [&plain, &fast] {
+        assert!(proof.verify(&r1, &r2, &y1, &y2, &c, &s).is_ok());
+        assert_eq!(
+            proof.verify(&r1, &r2, &y1, &y2, &s, &c),
+            Err(Error::VerificationFailed(Equation::R1))
+        );
+        assert_eq!(
+            proof.verify(&r1, &r1, &y1, &y2, &c, &s),
+            Err(Error::VerificationFailed(Equation::R2))
+        );
+        assert!(
+            proof
+                .verify_batch(&[transcript.clone(), transcript.clone()])
+                .is_ok()
+        );
+    }
+}
+
+#[test]
+fn test_tables_do_not_change_results() {
+    check_tables(modp_proof(), modp_proof().with_fixed_base_tables());
+
+    check_tables(
+        ristretto_proof(),
+        ristretto_proof().with_fixed_base_tables(),
+    );
+}
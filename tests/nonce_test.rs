@@ -0,0 +1,147 @@
+use cp_protocol::drbg::HmacDrbg;
+use cp_protocol::{Group, Nonce, Secret, SecretScalar};
+use num_bigint::BigUint;
+
+mod common;
+
+use common::{modp_proof, ristretto_proof};
+
+// cross checked against an independent HMAC_DRBG implementation
+#[test]
+fn test_hmac_drbg_vectors() {
+    let mut drbg = HmacDrbg::new(b"entropy input", b"nonce", b"");
+    let mut out = [0u8; 80];
+    drbg.generate(&mut out);
+    assert_eq!(
+        hex::encode(out),
+        "7404adbe7fe4286f0a1272d285c7c8a7d6b03859019e3ef46ff6b9e9a325c4cd\
+         b902d1c87e5df5aa4e9b3c25b145b97e221573f2d773cfaf286b7d46b82ed189\
+         f35723271b6c96283c4b75a56c376866"
+    );
+
+    let mut out = [0u8; 32];
+    drbg.generate(&mut out);
+    assert_eq!(
+        hex::encode(out),
+        "d6ce20743787567ecc41e41bcfa83560929b3bd697cf1ab72ccd784a80633f10"
+    );
+
+    let mut drbg = HmacDrbg::new(b"entropy input", b"nonce", b"personalization");
+    let mut out = [0u8; 64];
+    drbg.generate(&mut out);
+    assert_eq!(
+        hex::encode(out),
+        "5b32c105aa11ddc9b887b5dbc8c6d902814efcbad62bfe421a07ef70f37073ac\
+         3802084ce6bd4aa550d22ded702b1553ecccf33e7fee7f691a8ee8f70f7283d9"
+    );
+}
+
+// cross checked against an independent implementation, like the HMAC_DRBG vectors
+#[test]
+fn test_derived_nonce_vectors() {
+    let proof = modp_proof();
+    let group = proof.group();
+    let x = SecretScalar::new(BigUint::from(123456789u32));
+
+    let k = Nonce::derive(group, &x, b"context", None);
+    assert_eq!(
+        hex::encode(group.encode_scalar(k.expose_secret())),
+        "381a8c9c99f72703b3d4b98696d96a3ed5f671b09affc988f4d48610"
+    );
+
+    let k = Nonce::derive(group, &x, b"context", Some(b"extra entropy"));
+    assert_eq!(
+        hex::encode(group.encode_scalar(k.expose_secret())),
+        "1f8bd4015fe5acb52e4e953774130563bd610c5175d4280d218d6e30"
+    );
+
+    let proof = ristretto_proof();
+    let group = proof.group();
+    let x = SecretScalar::new(group.scalar_from_bytes(b"x"));
+
+    let k = Nonce::derive(group, &x, b"context", None);
+    assert_eq!(
+        hex::encode(group.encode_scalar(k.expose_secret())),
+        "370b3fa0764c616be9d76804c5332a72ac6d05c6ac528ef50b3b9ac80d6e8907"
+    );
+}
+
+#[test]
+fn test_derived_nonce_depends_on_every_input() {
+    let proof = ristretto_proof();
+    let group = proof.group();
+    let x = SecretScalar::new(group.scalar_from_bytes(b"x"));
+    let other = SecretScalar::new(group.scalar_from_bytes(b"other x"));
+
+    let k = *Nonce::derive(group, &x, b"context", None).expose_secret();
+    assert_eq!(
+        k,
+        *Nonce::derive(group, &x, b"context", None).expose_secret()
+    );
+
+    for different in [
+        Nonce::derive(group, &other, b"context", None),
+        Nonce::derive(group, &x, b"other context", None),
+        Nonce::derive(group, &x, b"context", Some(b"entropy")),
+    ] {
+        assert_ne!(k, *different.expose_secret());
+    }
+
+    // moving bytes between context and extra_entropy changes k too
+    let k = Nonce::derive(group, &x, b"ab", Some(b"c"));
+    let moved = Nonce::derive(group, &x, b"a", Some(b"bc"));
+    assert_ne!(k.expose_secret(), moved.expose_secret());
+}
+
+#[test]
+fn test_deterministic_non_interactive() {
+    let proof = modp_proof();
+    let x = SecretScalar::new(proof.generate_random_number());
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+    let nonce = 1700000000u64.to_be_bytes();
+
+    let first = proof
+        .prove_non_interactive_deterministic(&x, "alice", &nonce, None)
+        .unwrap();
+    let second = proof
+        .prove_non_interactive_deterministic(&x, "alice", &nonce, None)
+        .unwrap();
+
+    assert_eq!(first, second);
+    assert!(
+        proof
+            .verify_non_interactive(&y1, &y2, "alice", &nonce, &first)
+            .is_ok()
+    );
+
+    // a new timestamp must give a new commitment, otherwise x would leak
+    let later = proof
+        .prove_non_interactive_deterministic(&x, "alice", &1700000001u64.to_be_bytes(), None)
+        .unwrap();
+    assert_ne!(first.r1, later.r1);
+}
+
+#[test]
+fn test_commit_deterministic() {
+    let proof = ristretto_proof();
+    let x = SecretScalar::new(proof.generate_random_number());
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+
+    // the session is consumed, so it cannot hand out a second commitment with the same k
+    let commitment = proof
+        .prover(&x)
+        .commit_deterministic(b"session 1", Some(b"entropy"))
+        .unwrap();
+
+    // and fresh extra entropy gives a fresh k even when the context comes back
+    let restored = proof
+        .prover(&x)
+        .commit_deterministic(b"session 1", Some(b"other entropy"))
+        .unwrap();
+    assert_ne!(commitment.r1(), restored.r1());
+
+    let (r1, r2) = (*commitment.r1(), *commitment.r2());
+    let c = proof.generate_random_number();
+    let s = commitment.respond(&c).unwrap();
+    assert!(proof.verify(&r1, &r2, &y1, &y2, &c, &s).is_ok());
+}
@@ -0,0 +1,71 @@
+use cp_protocol::{
+    BETA_SEED, DEFAULT_PARAMETER_SET, Error, MinimumStrength, ParameterError, ParameterSet, Proof,
+};
+
+#[test]
+fn test_default_matches_get_constants() {
+    let set = ParameterSet::by_name(DEFAULT_PARAMETER_SET).unwrap();
+    assert_eq!(set.constants().unwrap(), Proof::get_constants().unwrap());
+}
+
+#[test]
+fn test_unknown_name() {
+    assert!(ParameterSet::by_name("rfc0000-1-1").is_none());
+}
+
+#[test]
+fn test_subgroup_sets_are_valid() {
+    for name in ["rfc5114-1024-160", "rfc5114-2048-224", "rfc5114-2048-256"] {
+        let set = ParameterSet::by_name(name).unwrap();
+        let minimum = MinimumStrength {
+            p_bits: 1024,
+            q_bits: 160,
+        };
+
+        let (p, q, alpha, beta) = set.constants().unwrap();
+        assert!(
+            set.group().unwrap().verify_generator(BETA_SEED, &beta),
+            "{}",
+            name
+        );
+        assert!(
+            Proof::try_new(p, q, alpha, beta, &minimum).is_ok(),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_safe_prime_sets_are_valid() {
+    for name in ["rfc3526-modp-2048", "rfc7919-ffdhe2048"] {
+        let set = ParameterSet::by_name(name).unwrap();
+        let group = set.group().unwrap();
+        assert_eq!(group.q().bits(), 2047, "{}", name);
+        assert!(set.proof(&MinimumStrength::default()).is_ok(), "{}", name);
+    }
+}
+
+#[test]
+fn test_every_set_is_registered_once() {
+    let names: Vec<_> = ParameterSet::names().collect();
+    assert_eq!(names.len(), 9);
+
+    for name in &names {
+        assert_eq!(names.iter().filter(|other| *other == name).count(), 1);
+        let (p, q, _, _) = ParameterSet::by_name(name).unwrap().constants().unwrap();
+        assert_eq!((p - 1u32) % q, num_bigint::BigUint::ZERO, "{}", name);
+    }
+}
+
+#[test]
+fn test_legacy_set_below_default_strength() {
+    let set = ParameterSet::by_name("rfc5114-1024-160").unwrap();
+    assert_eq!(
+        set.proof(&MinimumStrength::default()).err(),
+        Some(Error::InvalidParameters(ParameterError::ModulusTooSmall {
+            bits: 1024,
+            minimum: 2048
+        }))
+    );
+}
@@ -0,0 +1,134 @@
+use cp_protocol::params::is_probable_prime;
+use cp_protocol::{BETA_SEED, Error, MinimumStrength, ModpGroup, ParameterError, Proof};
+use num_bigint::BigUint;
+
+fn small(p: u32, q: u32, alpha: u32, beta: u32) -> Result<Proof, Error> {
+    Proof::try_new(
+        BigUint::from(p),
+        BigUint::from(q),
+        BigUint::from(alpha),
+        BigUint::from(beta),
+        &MinimumStrength::none(),
+    )
+}
+
+#[test]
+fn test_get_constants_are_valid() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let result = Proof::try_new(p, q, alpha, beta, &MinimumStrength::default());
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_minimum_strength() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let minimum = MinimumStrength {
+        p_bits: 3072,
+        q_bits: 256,
+    };
+
+    let result = Proof::try_new(p, q, alpha, beta, &minimum);
+    assert_eq!(
+        result.err(),
+        Some(Error::InvalidParameters(ParameterError::ModulusTooSmall {
+            bits: 2048,
+            minimum: 3072
+        }))
+    );
+
+    let result = Proof::try_new(
+        BigUint::from(23u32),
+        BigUint::from(11u32),
+        BigUint::from(4u32),
+        BigUint::from(9u32),
+        &MinimumStrength::default(),
+    );
+    assert!(matches!(
+        result.err(),
+        Some(Error::InvalidParameters(
+            ParameterError::ModulusTooSmall { .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_small_parameters() {
+    assert!(small(23, 11, 4, 9).is_ok());
+
+    assert_eq!(
+        small(25, 11, 4, 9).err(),
+        Some(Error::InvalidParameters(ParameterError::CompositeModulus))
+    );
+    assert_eq!(
+        small(23, 9, 4, 9).err(),
+        Some(Error::InvalidParameters(ParameterError::CompositeOrder))
+    );
+    assert_eq!(
+        small(29, 11, 4, 9).err(),
+        Some(Error::InvalidParameters(ParameterError::OrderDoesNotDivide))
+    );
+    assert_eq!(
+        small(23, 11, 1, 9).err(),
+        Some(Error::InvalidParameters(ParameterError::InvalidGenerator(
+            "alpha"
+        )))
+    );
+    // 5 generates the full group of order 22
+    assert_eq!(
+        small(23, 11, 4, 5).err(),
+        Some(Error::InvalidParameters(ParameterError::InvalidGenerator(
+            "beta"
+        )))
+    );
+    assert_eq!(
+        small(23, 11, 4, 23).err(),
+        Some(Error::InvalidParameters(ParameterError::InvalidGenerator(
+            "beta"
+        )))
+    );
+}
+
+#[test]
+fn test_is_probable_prime() {
+    for prime in [2u32, 3, 89, 97, 7919, 2147483647] {
+        assert!(is_probable_prime(&BigUint::from(prime)), "{}", prime);
+    }
+    // 561 is a Carmichael number
+    for composite in [0u32, 1, 4, 561, 7917, 2147483649] {
+        assert!(
+            !is_probable_prime(&BigUint::from(composite)),
+            "{}",
+            composite
+        );
+    }
+}
+
+#[test]
+fn test_derived_generator() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let group = ModpGroup::new(p.clone(), q.clone());
+
+    // beta in the default constants is reproducible from its seed
+    assert!(group.verify_generator(BETA_SEED, &beta));
+    assert!(!group.verify_generator(b"another seed", &beta));
+    assert!(!group.verify_generator(BETA_SEED, &alpha));
+
+    let (derived, counter) = group.derive_generator(b"another seed");
+    assert_eq!(
+        group.derive_generator(b"another seed"),
+        (derived.clone(), counter)
+    );
+    assert_ne!(derived, beta);
+    assert!(group.validate_generator("derived", &derived).is_ok());
+}
+
+#[test]
+fn test_derived_generator_small_group() {
+    let group = ModpGroup::new(BigUint::from(23u32), BigUint::from(11u32));
+
+    for seed in [&b"a"[..], b"b", b"c", b"d"] {
+        let (generator, _) = group.derive_generator(seed);
+        assert!(group.validate_generator("derived", &generator).is_ok());
+        assert!(group.verify_generator(seed, &generator));
+    }
+}
@@ -0,0 +1,67 @@
+use cp_protocol::kdf::{Kdf, derive_secret, generate_salt_with_rng};
+use cp_protocol::{Group, Proof, Ristretto255};
+use rand::{SeedableRng, rngs::StdRng};
+
+// registration and one interactive login, every random value drawn from a single seeded RNG
+fn simulate<G: Group>(proof: &Proof<G>, seed: u64) -> Vec<Vec<u8>> {
+    let group = proof.group();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let kdf = Kdf::Pbkdf2Sha512 { iterations: 1 };
+
+    let salt = generate_salt_with_rng(&mut rng);
+    let x = derive_secret(group, &kdf, b"password", &salt).unwrap();
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+
+    let commitment = proof.prover(&x).commit_with_rng(&mut rng).unwrap();
+    let (r1, r2) = (commitment.r1().clone(), commitment.r2().clone());
+    let c = proof.generate_random_number_with_rng(&mut rng);
+    let s = commitment.respond(&c).unwrap();
+    assert!(proof.verify(&r1, &r2, &y1, &y2, &c, &s).is_ok());
+
+    let login = proof
+        .prove_non_interactive_with_rng(&x, "alice", b"nonce", &mut rng)
+        .unwrap();
+    assert!(
+        proof
+            .verify_non_interactive(&y1, &y2, "alice", b"nonce", &login)
+            .is_ok()
+    );
+
+    vec![
+        salt,
+        group.encode_element(&r1),
+        group.encode_element(&r2),
+        group.encode_scalar(&c),
+        group.encode_scalar(&s),
+        group.encode_element(&login.r1),
+        group.encode_scalar(&login.s),
+    ]
+}
+
+#[test]
+fn test_seeded_simulation_is_reproducible() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha, beta);
+
+    assert_eq!(simulate(&proof, 7), simulate(&proof, 7));
+    assert_ne!(simulate(&proof, 7), simulate(&proof, 8));
+}
+
+#[test]
+fn test_seeded_simulation_is_reproducible_ristretto() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+
+    assert_eq!(simulate(&proof, 7), simulate(&proof, 7));
+    assert_ne!(simulate(&proof, 7), simulate(&proof, 8));
+}
+
+#[test]
+fn test_random_string_uses_the_given_rng() {
+    let mut a = StdRng::seed_from_u64(1);
+    let mut b = StdRng::seed_from_u64(1);
+    let id = Proof::generate_random_string_below_with_rng(12, &mut a);
+
+    assert_eq!(id.len(), 12);
+    assert_eq!(id, Proof::generate_random_string_below_with_rng(12, &mut b));
+}
@@ -0,0 +1,128 @@
+use cp_protocol::{Equation, Error, Group, Proof, Ristretto255, SchnorrProof, SecretScalar};
+use num_bigint::BigUint;
+
+#[test]
+fn test_schnorr() {
+    let proof = Proof::new(
+        BigUint::from(23u32),
+        BigUint::from(11u32),
+        BigUint::from(4u32),
+        BigUint::from(9u32),
+    );
+    let schnorr = proof.schnorr();
+
+    // the alpha half of the Chaum-Pedersen example in lib_test
+    let x = SecretScalar::new(BigUint::from(6u32));
+    let y1 = schnorr.public_key(&x).unwrap();
+    assert_eq!(y1, BigUint::from(2u32));
+    assert_eq!(y1, proof.create_pair(&x).unwrap()[0]);
+
+    let commitment = schnorr.prover(&x).commit().unwrap();
+    let r1 = commitment.r1().clone();
+    let c = BigUint::from(4u32);
+    let s = commitment.respond(&c).unwrap();
+    assert!(schnorr.verify(&r1, &y1, &c, &s).is_ok());
+
+    let wrong = BigUint::from(5u32);
+    assert_eq!(
+        schnorr.verify(&r1, &y1, &wrong, &s),
+        Err(Error::VerificationFailed(Equation::R1))
+    );
+    // 22 = -1 is not in the subgroup
+    assert_eq!(
+        schnorr.verify(&BigUint::from(22u32), &y1, &c, &s),
+        Err(Error::InvalidElement("r1"))
+    );
+    assert_eq!(
+        schnorr.verify(&r1, &BigUint::from(22u32), &c, &s),
+        Err(Error::InvalidElement("y1"))
+    );
+    assert_eq!(
+        schnorr.verify(&r1, &y1, &c, &BigUint::from(11u32)),
+        Err(Error::ScalarOutOfRange("s"))
+    );
+}
+
+fn check_non_interactive<G: Group>(proof: &Proof<G>) {
+    let schnorr = proof.schnorr();
+    let x = SecretScalar::new(proof.generate_random_number());
+    let y1 = schnorr.public_key(&x).unwrap();
+
+    let login = schnorr
+        .prove_non_interactive(&x, "alice", b"nonce")
+        .unwrap();
+    assert!(
+        schnorr
+            .verify_non_interactive(&y1, "alice", b"nonce", &login)
+            .is_ok()
+    );
+    for (identifier, nonce) in [("bob", &b"nonce"[..]), ("alice", b"other nonce")] {
+        assert_eq!(
+            schnorr.verify_non_interactive(&y1, identifier, nonce, &login),
+            Err(Error::VerificationFailed(Equation::R1))
+        );
+    }
+
+    let other = SecretScalar::new(proof.generate_random_number());
+    let forged = schnorr
+        .prove_non_interactive(&other, "alice", b"nonce")
+        .unwrap();
+    assert!(
+        schnorr
+            .verify_non_interactive(&y1, "alice", b"nonce", &forged)
+            .is_err()
+    );
+
+    // deterministic proofs repeat for the same statement only
+    let first = schnorr
+        .prove_non_interactive_deterministic(&x, "alice", b"nonce", None)
+        .unwrap();
+    let second = schnorr
+        .prove_non_interactive_deterministic(&x, "alice", b"nonce", None)
+        .unwrap();
+    assert_eq!((&first.r1, &first.s), (&second.r1, &second.s));
+    assert!(
+        schnorr
+            .verify_non_interactive(&y1, "alice", b"nonce", &first)
+            .is_ok()
+    );
+    let other_nonce = schnorr
+        .prove_non_interactive_deterministic(&x, "alice", b"other nonce", None)
+        .unwrap();
+    assert_ne!(first.r1, other_nonce.r1);
+}
+
+#[test]
+fn test_schnorr_non_interactive() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha, beta);
+    check_non_interactive(&proof);
+    check_non_interactive(&proof.with_fixed_base_tables());
+
+    let (alpha, beta) = Ristretto255::get_constants();
+    check_non_interactive(&Proof::with_group(Ristretto255, alpha, beta));
+}
+
+// the alpha half of a Chaum-Pedersen transcript is a Schnorr transcript, but the two
+// protocols hash different challenges, so a login proof of one never logs in with the other
+#[test]
+fn test_schnorr_and_chaum_pedersen_challenges_differ() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+    let schnorr = proof.schnorr();
+    let x = SecretScalar::new(proof.generate_random_number());
+    let [y1, y2] = proof.create_pair(&x).unwrap();
+
+    let login = proof.prove_non_interactive(&x, "alice", b"nonce").unwrap();
+    let c = proof.challenge(&y1, &y2, &login.r1, &login.r2, "alice", b"nonce");
+    assert!(schnorr.verify(&login.r1, &y1, &c, &login.s).is_ok());
+
+    let half = SchnorrProof {
+        r1: login.r1,
+        s: login.s,
+    };
+    assert_eq!(
+        schnorr.verify_non_interactive(&y1, "alice", b"nonce", &half),
+        Err(Error::VerificationFailed(Equation::R1))
+    );
+}
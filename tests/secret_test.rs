@@ -0,0 +1,39 @@
+use cp_protocol::{Group, ModpGroup, Nonce, Proof, Ristretto255, Secret, SecretScalar};
+use curve25519_dalek::scalar::Scalar;
+use num_bigint::BigUint;
+use rand_core::OsRng;
+
+#[test]
+fn test_debug_is_redacted() {
+    let x: SecretScalar<ModpGroup> = SecretScalar::new(BigUint::from(123456789u32));
+    let group = ModpGroup::new(BigUint::from(23u32), BigUint::from(11u32));
+    let k = Nonce::random(&group, &mut OsRng);
+
+    assert_eq!(format!("{:?}", x), "SecretScalar(<redacted>)");
+    assert_eq!(format!("{:?}", k), "Nonce(<redacted>)");
+    assert_eq!(*x.expose_secret(), BigUint::from(123456789u32));
+}
+
+#[test]
+fn test_nonce_is_never_zero() {
+    // one in 11 draws below q would be zero
+    let group = ModpGroup::new(BigUint::from(23u32), BigUint::from(11u32));
+    for _ in 0..200 {
+        let k = Nonce::random(&group, &mut OsRng);
+        assert_ne!(*k.expose_secret(), BigUint::ZERO);
+    }
+}
+
+#[test]
+fn test_zeroize_scalar() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha, beta);
+
+    let mut scalar = proof.generate_random_number();
+    ModpGroup::zeroize_scalar(&mut scalar);
+    assert_eq!(scalar, BigUint::ZERO);
+
+    let mut scalar = Ristretto255.random_scalar(&mut OsRng);
+    Ristretto255::zeroize_scalar(&mut scalar);
+    assert_eq!(scalar, Scalar::ZERO);
+}
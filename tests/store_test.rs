@@ -0,0 +1,410 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+use cp_protocol::Kdf;
+use cp_protocol::store::{
+    CredentialStore, FileStore, MAX_COMMITMENTS, MemoryStore, Registration, Session, SessionStore,
+    StoreError, session_hash,
+};
+
+fn registration(identifier: &str, y2: Option<Vec<u8>>) -> Registration {
+    Registration::new(
+        identifier.to_string(),
+        vec![1, 2, 3],
+        y2,
+        Kdf::Scrypt {
+            log_n: 10,
+            r: 8,
+            p: 1,
+        },
+        vec![7; 16],
+    )
+}
+
+fn session(session_id: &str, identifier: &str, expires_at: u64) -> Session {
+    Session {
+        session_id: session_id.to_string(),
+        identifier: identifier.to_string(),
+        created_at: 100,
+        expires_at,
+    }
+}
+
+// a fresh path under the system temp directory, removed before the test starts
+fn temporary_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("cp-protocol-{}-{}", std::process::id(), name));
+    remove(&path);
+    path
+}
+
+// the file store keeps its commitments and sessions next to the path
+fn remove(path: &PathBuf) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.commitments", path.display()));
+    let _ = fs::remove_file(format!("{}.sessions", path.display()));
+}
+
+fn check_store(store: &impl CredentialStore) {
+    let alice = registration("alice", Some(vec![4, 5, 6]));
+    let bob = registration("bob", None);
+
+    assert_eq!(store.get("alice").unwrap(), None);
+    store.put(bob.clone()).unwrap();
+    store.put(alice.clone()).unwrap();
+    assert_eq!(store.get("alice").unwrap(), Some(alice.clone()));
+    assert_eq!(store.list().unwrap(), vec![alice.clone(), bob.clone()]);
+
+    // compare_and_set only writes over the registration it expects
+    let mut flagged = alice.clone();
+    flagged.compromised = true;
+    assert!(!store.compare_and_set(None, flagged.clone()).unwrap());
+    assert!(
+        store
+            .compare_and_set(Some(&alice), flagged.clone())
+            .unwrap()
+    );
+    assert!(!store.compare_and_set(Some(&alice), alice.clone()).unwrap());
+    assert_eq!(store.get("alice").unwrap(), Some(flagged));
+
+    let carol = registration("carol", None);
+    assert!(store.compare_and_set(None, carol.clone()).unwrap());
+    assert!(!store.compare_and_set(None, carol).unwrap());
+
+    assert_eq!(store.update("dave", |_| ()).unwrap(), None);
+    assert_eq!(
+        store
+            .update("bob", |registration| registration.record_transcript(
+                vec![9],
+                vec![],
+                vec![1],
+                vec![2]
+            ))
+            .unwrap(),
+        Some(())
+    );
+    assert_eq!(store.get("bob").unwrap().unwrap().commitments.len(), 1);
+
+    assert!(store.delete("bob").unwrap());
+    assert!(!store.delete("bob").unwrap());
+    assert_eq!(store.get("bob").unwrap(), None);
+}
+
+fn check_sessions(store: &impl SessionStore) {
+    store.put_session(session("one", "alice", 200)).unwrap();
+    store.put_session(session("two", "alice", 300)).unwrap();
+    store.put_session(session("three", "bob", 300)).unwrap();
+
+    // valid while now < expires_at
+    assert_eq!(
+        store.get_session("one", 199).unwrap(),
+        Some(session("one", "alice", 200))
+    );
+    assert_eq!(store.get_session("one", 200).unwrap(), None);
+    assert_eq!(store.get_session("four", 0).unwrap(), None);
+
+    // an expired session cannot be brought back
+    assert_eq!(store.refresh_session("one", 200, 400).unwrap(), None);
+    assert_eq!(
+        store.refresh_session("two", 250, 400).unwrap(),
+        Some(session("two", "alice", 400))
+    );
+    assert_eq!(
+        store.get_session("two", 399).unwrap().unwrap().expires_at,
+        400
+    );
+
+    assert_eq!(store.delete_expired_sessions(300).unwrap(), 2);
+    assert_eq!(store.delete_sessions_of("alice").unwrap(), 1);
+    assert_eq!(store.get_session("two", 0).unwrap(), None);
+
+    store.put_session(session("five", "bob", 500)).unwrap();
+    assert!(store.delete_session("five").unwrap());
+    assert!(!store.delete_session("five").unwrap());
+}
+
+#[test]
+fn test_stores() {
+    check_store(&MemoryStore::new());
+    check_sessions(&MemoryStore::new());
+
+    let path = temporary_path("stores");
+    check_store(&FileStore::open(&path).unwrap());
+    check_sessions(&FileStore::open(&path).unwrap());
+    remove(&path);
+}
+
+#[test]
+fn test_file_store_persists() {
+    let path = temporary_path("persists");
+    let store = FileStore::open(&path).unwrap();
+
+    let mut alice = registration("alice smith", Some(vec![4, 5, 6]));
+    alice.kdf = Kdf::Argon2id {
+        memory_kib: 19456,
+        iterations: 2,
+        parallelism: 1,
+    };
+    alice.record_transcript(vec![10], vec![11], vec![12], vec![13]);
+    alice.record_transcript(vec![14], vec![15], vec![12], vec![16]);
+    let mut bob = registration("bob", None);
+    bob.kdf = Kdf::Pbkdf2Sha512 { iterations: 600000 };
+    bob.record_transcript(vec![16], vec![], vec![17], vec![18]);
+    bob.compromised = true;
+
+    store.put(alice.clone()).unwrap();
+    store.put(bob.clone()).unwrap();
+    store
+        .put_session(session("Xy12", "alice smith", 200))
+        .unwrap();
+    drop(store);
+
+    let store = FileStore::open(&path).unwrap();
+    assert_eq!(store.list().unwrap(), vec![alice, bob]);
+    assert_eq!(
+        store.get_session("Xy12", 100).unwrap(),
+        Some(session("Xy12", "alice smith", 200))
+    );
+
+    // a file this version did not write is refused rather than read as empty
+    fs::write(&path, "cp-protocol credentials v2\n").unwrap();
+    assert!(matches!(
+        FileStore::open(&path),
+        Err(StoreError::Corrupt(_))
+    ));
+    fs::write(&path, "cp-protocol credentials v1\nnot a registration\n").unwrap();
+    assert!(matches!(
+        FileStore::open(&path),
+        Err(StoreError::Corrupt(_))
+    ));
+    remove(&path);
+}
+
+#[test]
+fn test_file_store_hashes_sessions() {
+    let path = temporary_path("hashes-sessions");
+    let sessions_path = format!("{}.sessions", path.display());
+    let store = FileStore::open(&path).unwrap();
+    store
+        .put_session(session("Xy12-secret", "alice", 200))
+        .unwrap();
+
+    // the file holds the hash, never the id in any form
+    let contents = fs::read_to_string(&sessions_path).unwrap();
+    assert!(contents.contains(&session_hash("Xy12-secret")));
+    assert!(!contents.contains("Xy12-secret"));
+    assert!(!contents.contains(&hex::encode("Xy12-secret")));
+
+    // so the hash read from it does not open the session
+    assert_eq!(
+        store
+            .get_session(&session_hash("Xy12-secret"), 100)
+            .unwrap(),
+        None
+    );
+    drop(store);
+
+    // and a line that is not a hash is refused
+    fs::write(
+        &sessions_path,
+        format!(
+            "cp-protocol sessions v1\n{} 626f62 100 200\n",
+            hex::encode("Ab34")
+        ),
+    )
+    .unwrap();
+    assert!(matches!(
+        FileStore::open(&path),
+        Err(StoreError::Corrupt(_))
+    ));
+    remove(&path);
+}
+
+#[test]
+fn test_file_store_commitment_log() {
+    let path = temporary_path("commitment-log");
+    let log_path = format!("{}.commitments", path.display());
+    let store = FileStore::open(&path).unwrap();
+    store.put(registration("bob", Some(vec![4, 5, 6]))).unwrap();
+    let file_length = fs::metadata(&path).unwrap().len();
+
+    // commitments only reach the log, the registrations are not rewritten for them
+    for r1 in 0..MAX_COMMITMENTS as u8 + 5 {
+        store
+            .update("bob", |bob| {
+                bob.record_transcript(vec![r1], vec![r1, 1], vec![2], vec![r1])
+            })
+            .unwrap();
+    }
+    assert_eq!(fs::metadata(&path).unwrap().len(), file_length);
+
+    // replaying the log gives back the same commitments, even after a torn append
+    let bob = store.get("bob").unwrap().unwrap();
+    assert_eq!(bob.commitments.len(), MAX_COMMITMENTS);
+    drop(store);
+    let mut log = fs::OpenOptions::new().append(true).open(&log_path).unwrap();
+    std::io::Write::write_all(&mut log, b"626f62 6363:").unwrap();
+    let store = FileStore::open(&path).unwrap();
+    assert_eq!(store.get("bob").unwrap(), Some(bob));
+
+    // registering again starts over
+    store.put(registration("bob", None)).unwrap();
+    drop(store);
+    let store = FileStore::open(&path).unwrap();
+    assert_eq!(store.get("bob").unwrap(), Some(registration("bob", None)));
+
+    // and the log is compacted as it grows
+    for index in 0..1100u32 {
+        store
+            .update("bob", |bob| {
+                bob.record_transcript(index.to_be_bytes().to_vec(), vec![], vec![2], vec![3])
+            })
+            .unwrap();
+    }
+    assert!(fs::read_to_string(&log_path).unwrap().lines().count() < 200);
+    drop(store);
+    remove(&path);
+}
+
+#[test]
+fn test_commitments() {
+    let mut alice = registration("alice", Some(vec![4, 5, 6]));
+    alice.record_transcript(vec![1], vec![2], vec![5], vec![6]);
+    assert!(alice.seen_commitment(&[1], &[3]));
+    assert!(alice.seen_commitment(&[3], &[2]));
+    assert!(!alice.seen_commitment(&[3], &[4]));
+
+    // the same answer twice is a replay, a second challenge for one commitment leaks x
+    alice.record_transcript(vec![1], vec![2], vec![5], vec![6]);
+    assert!(!alice.compromised);
+    alice.record_transcript(vec![1], vec![2], vec![7], vec![8]);
+    assert!(alice.compromised);
+
+    // empty Schnorr r2s never match each other
+    let mut bob = registration("bob", None);
+    bob.record_transcript(vec![1], vec![], vec![5], vec![6]);
+    assert!(!bob.seen_commitment(&[2], &[]));
+
+    for r1 in 0..MAX_COMMITMENTS as u8 + 1 {
+        bob.record_transcript(vec![r1 + 2], vec![], vec![5], vec![6]);
+    }
+    assert_eq!(bob.commitments.len(), MAX_COMMITMENTS);
+    assert!(!bob.seen_commitment(&[1], &[]));
+
+    // concurrent updates through compare_and_set never lose one another
+    let store = Arc::new(MemoryStore::new());
+    store.put(bob.clone()).unwrap();
+    let threads: Vec<_> = (0..8u8)
+        .map(|thread| {
+            let store = store.clone();
+            thread::spawn(move || {
+                for index in 0..4u8 {
+                    store
+                        .update("bob", |registration| {
+                            registration.salt.push(thread * 4 + index)
+                        })
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(store.get("bob").unwrap().unwrap().salt.len(), 16 + 32);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_store() {
+    use cp_protocol::store::sqlite::SqliteStore;
+
+    let path = temporary_path("sqlite");
+    check_store(&SqliteStore::open(&path, "ristretto255").unwrap());
+    check_sessions(&SqliteStore::open(&path, "ristretto255").unwrap());
+
+    // registrations survive reopening, and stay apart per group
+    let store = SqliteStore::open(&path, "ristretto255").unwrap();
+    let mut alice = registration("alice", Some(vec![4, 5, 6]));
+    alice.record_transcript(vec![10], vec![11], vec![12], vec![13]);
+    alice.record_transcript(vec![14], vec![], vec![15], vec![16]);
+    store.put(alice.clone()).unwrap();
+    drop(store);
+
+    let store = SqliteStore::open(&path, "ristretto255").unwrap();
+    assert_eq!(store.get("alice").unwrap(), Some(alice.clone()));
+    let other = SqliteStore::open(&path, "modp/rfc5114-2048-224").unwrap();
+    assert_eq!(other.get("alice").unwrap(), None);
+    other.put(registration("alice", None)).unwrap();
+    assert_eq!(store.get("alice").unwrap(), Some(alice));
+    assert!(other.delete("alice").unwrap());
+    assert_eq!(store.list().unwrap().len(), 2);
+
+    // sessions too
+    store.put_session(session("one", "alice", 200)).unwrap();
+    assert_eq!(other.get_session("one", 100).unwrap(), None);
+    assert!(!other.delete_session("one").unwrap());
+    drop(store);
+    let store = SqliteStore::open(&path, "ristretto255").unwrap();
+    assert_eq!(
+        store.get_session("one", 100).unwrap(),
+        Some(session("one", "alice", 200))
+    );
+    fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_schema() {
+    use cp_protocol::store::sqlite::SqliteStore;
+    use rusqlite::Connection;
+
+    let path = temporary_path("schema");
+    let store = SqliteStore::open(&path, "ristretto255").unwrap();
+    let mut bob = registration("bob", None);
+    bob.record_transcript(vec![1], vec![], vec![2], vec![3]);
+    store.put(bob).unwrap();
+    drop(store);
+
+    // plain SQL sees the migrated schema and readable columns
+    let connection = Connection::open(&path).unwrap();
+    let version: usize = connection
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap();
+    assert_eq!(version, SqliteStore::schema_version());
+    let (y2, kdf): (Option<Vec<u8>>, String) = connection
+        .query_row(
+            "SELECT y2, kdf FROM registrations WHERE identifier = 'bob'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((y2, kdf.as_str()), (None, "scrypt:10:8:1"));
+    let commitments: usize = connection
+        .query_row("SELECT count(*) FROM commitments", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(commitments, 1);
+
+    // sessions are kept under the hash of their id
+    let store = SqliteStore::open(&path, "ristretto255").unwrap();
+    store
+        .put_session(session("Xy12-secret", "bob", 200))
+        .unwrap();
+    drop(store);
+    let hash: String = connection
+        .query_row("SELECT session_hash FROM sessions", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(hash, session_hash("Xy12-secret"));
+
+    // a database migrated by a newer verifier is left alone
+    connection
+        .pragma_update(None, "user_version", SqliteStore::schema_version() + 1)
+        .unwrap();
+    drop(connection);
+    assert!(matches!(
+        SqliteStore::open(&path, "ristretto255"),
+        Err(StoreError::Corrupt(_))
+    ));
+    fs::remove_file(path).unwrap();
+}
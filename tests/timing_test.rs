@@ -0,0 +1,126 @@
+use std::time::Instant;
+
+use cp_protocol::hazmat;
+use cp_protocol::{Group, Proof, Ristretto255, SecretScalar};
+use num_bigint::BigUint;
+use rand_core::{OsRng, RngCore};
+
+// dudect style leakage tests: time an operation on a fixed secret against fresh random
+// secrets, interleaved in random order, and compare the two distributions with Welch's
+// t-test. they are slow and noisy on a busy machine, so they only run on demand:
+//
+//   cargo test --release --features hazmat --test timing_test -- --ignored --test-threads=1
+//
+// DUDECT_SAMPLES overrides the number of measurements per test
+
+// |t| above this is the usual dudect threshold for a definite leak
+const T_THRESHOLD: f64 = 4.5;
+
+// measurements above this percentile are dropped, they are mostly interrupts
+const CROP_PERCENTILE: f64 = 0.9;
+
+fn samples() -> usize {
+    std::env::var("DUDECT_SAMPLES")
+        .ok()
+        .and_then(|samples| samples.parse().ok())
+        .unwrap_or(2000)
+}
+
+// Welch's t statistic between the timings of the fixed and the random class
+fn welch_t(timings: &[(bool, f64)]) -> f64 {
+    let mut sorted: Vec<f64> = timings.iter().map(|(_, time)| *time).collect();
+    sorted.sort_by(f64::total_cmp);
+    let crop = sorted[((sorted.len() - 1) as f64 * CROP_PERCENTILE) as usize];
+
+    let moments = |class: bool| {
+        let times: Vec<f64> = timings
+            .iter()
+            .filter(|(fixed, time)| *fixed == class && *time <= crop)
+            .map(|(_, time)| *time)
+            .collect();
+        let n = times.len() as f64;
+        let mean = times.iter().sum::<f64>() / n;
+        let variance = times.iter().map(|time| (time - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (n, mean, variance)
+    };
+
+    let (n0, mean0, variance0) = moments(true);
+    let (n1, mean1, variance1) = moments(false);
+    (mean0 - mean1) / (variance0 / n0 + variance1 / n1).sqrt()
+}
+
+// times operation on the fixed secret or a random one, chosen by a coin flip per sample
+fn measure<S>(fixed: &S, random: impl Fn() -> S, operation: impl Fn(&S)) -> f64 {
+    let mut timings = Vec::with_capacity(samples());
+    for _ in 0..samples() {
+        let class = OsRng.next_u32() & 1 == 0;
+        let input = if class { None } else { Some(random()) };
+        let secret = input.as_ref().unwrap_or(fixed);
+
+        let start = Instant::now();
+        operation(secret);
+        timings.push((class, start.elapsed().as_nanos() as f64));
+    }
+    welch_t(&timings)
+}
+
+// every operation on x or k, with a fixed secret of one, the cheapest input for any
+// exponentiation or multiplication that skips work on zero bits
+fn check_secret_operations<G: Group>(proof: &Proof<G>, fixed: G::Scalar) {
+    let fixed_x = SecretScalar::new(fixed.clone());
+    let random_x = || SecretScalar::new(proof.generate_random_number());
+    let t = measure(&fixed_x, random_x, |x| {
+        proof.create_pair(x).unwrap();
+    });
+    assert!(t.abs() < T_THRESHOLD, "create_pair leaks, t = {t}");
+
+    let x = SecretScalar::new(proof.generate_random_number());
+    let c = proof.generate_random_number();
+    let t = measure(
+        &fixed,
+        || proof.generate_random_number(),
+        |k| {
+            hazmat::solve(proof, hazmat::nonce(k.clone()), &c, &x).unwrap();
+        },
+    );
+    assert!(t.abs() < T_THRESHOLD, "solve leaks through k, t = {t}");
+
+    let k = proof.generate_random_number();
+    let t = measure(&fixed_x, random_x, |x| {
+        hazmat::solve(proof, hazmat::nonce(k.clone()), &c, x).unwrap();
+    });
+    assert!(t.abs() < T_THRESHOLD, "solve leaks through x, t = {t}");
+}
+
+#[test]
+#[ignore]
+fn test_modp_secret_operations_are_constant_time() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha, beta);
+    check_secret_operations(&proof, BigUint::from(1u32));
+}
+
+#[test]
+#[ignore]
+fn test_ristretto_secret_operations_are_constant_time() {
+    let (alpha, beta) = Ristretto255::get_constants();
+    let proof = Proof::with_group(Ristretto255, alpha, beta);
+    check_secret_operations(&proof, curve25519_dalek::Scalar::ONE);
+}
+
+// the harness must be able to see the leak it is meant to catch: public exponentiation is
+// plain modpow, whose running time follows the length of the exponent
+#[test]
+#[ignore]
+fn test_harness_detects_variable_time_exp() {
+    let (p, q, alpha, beta) = Proof::get_constants().unwrap();
+    let proof = Proof::new(p, q, alpha.clone(), beta);
+    let t = measure(
+        &BigUint::from(1u32),
+        || proof.generate_random_number(),
+        |exp| {
+            proof.group().exp(&alpha, exp);
+        },
+    );
+    assert!(t.abs() >= T_THRESHOLD, "no leak found in modpow, t = {t}");
+}